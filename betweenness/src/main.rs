@@ -1,59 +1,317 @@
-use std::collections::HashMap;
+use std::env;
 
-use petgraph::algo::astar;
+use graphcore::connectivity::{Connectivity, Measure};
+use graphcore::examples;
+use graphcore::paths::{for_each_source, ShortestPaths};
+use graphcore::scores::{Format, Scores};
+use petgraph::graph::EdgeReference;
 use petgraph::prelude::*;
-use num_rational::{self, Rational32};
+use petgraph::EdgeType;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, Num, Zero};
+
+mod sampling;
+
+use sampling::{approximate_betweenness, Pivots, Sampling};
+
+const USAGE: &str = "Usage: betweenness [table|csv|json]";
 
 fn main() {
-    let mut graph: DiGraph<&str, f32> = Graph::new();
-    let a = graph.add_node("Alice");
-    let b = graph.add_node("Bob");
-    let c = graph.add_node("Carol");
-    let d = graph.add_node("Dan");
-    let e = graph.add_node("Eve");
-    let f = graph.add_node("Frank");
-    let g = graph.add_node("Gale");
-    graph.extend_with_edges(&[
-        (a, c, 1.0), (b, c, 1.0), (c, d, 1.0),
-        (c, e, 1.3), (d, f, 1.0), (e, f, 0.5),
-        (f, g, 1.0),
-    ]);
-    let graph = graph;
-    let result = betweenness(&graph);
-    let lo = result.values().min().unwrap();
-    let hi = result.values().max().unwrap();
-    for v in result.keys() {
-        println!("{:?}: {}", v, (result.get(v).unwrap() - lo) / (hi - lo));
-    }
-    // not quite. :-( 
-}
-
-fn betweenness(graph: &DiGraph<&str, f32>) -> HashMap<NodeIndex, Rational32> {
-    let mut result = HashMap::new();
-    for x in graph.node_indices() {
-        let mut paths_with_x = 0;
-        let mut paths_total = 0;
-        for s in graph.node_indices() {
-            if x == s {
-                continue
+    let format = Format::from_arg(env::args().nth(1).as_deref(), USAGE);
+
+    let graph = alice_gale();
+    Connectivity::of(&graph).warn(Measure::Betweenness);
+    // Same numbers as gds.betweenness.stream and graphrs in rsgraph. The
+    // weights are summed as f32, so paths tie exactly when their f32 lengths
+    // are equal.
+    let raw: Scores<_, BigRational> = betweenness(&graph, |e| *e.weight(), Options::default());
+    let normalized: Scores<_, BigRational> = betweenness(&graph, |e| *e.weight(), Options {
+        normalization: Normalization::Normalized,
+        ..Default::default()
+    });
+    let min_max: Scores<_, f64> = betweenness(&graph, |e| *e.weight(), Options {
+        normalization: Normalization::MinMax,
+        ..Default::default()
+    });
+    print!("{}", raw.render(format, "raw"));
+    println!();
+    print!("{}", normalized.render(format, "normalized"));
+    println!();
+    print!("{}", min_max.render(format, "min-max"));
+
+    // Estimate from three of the seven vertices instead of all of them.
+    let sampling = Sampling { size: 3, seed: 7, pivots: Pivots::Uniform };
+    let approx = approximate_betweenness(&graph, |e| *e.weight(), sampling, Options::default());
+    println!();
+    print!("{}", approx.render(format, "estimate"));
+}
+
+/// `graphcore::examples::alice_gale` with `f32` weights.
+fn alice_gale() -> DiGraph<&'static str, f32> {
+    examples::alice_gale().map(|_, &name| name, |_, &w| w as f32)
+}
+
+/// How the betweenness scores are scaled after accumulation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Normalization {
+    /// Number of shortest paths through each vertex. Undirected graphs count
+    /// each pair once, not once in each direction.
+    #[default]
+    Raw,
+    /// Divide by the number of pairs that could pass through a vertex:
+    /// (n-1)(n-2) for directed graphs and half that for undirected graphs, or
+    /// n(n-1) (halved for undirected) when endpoints are included. This is what
+    /// graphrs and networkx return with `normalized = true`.
+    Normalized,
+    /// Rescale to [0, 1] using the smallest and largest raw scores.
+    MinMax,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Options {
+    normalization: Normalization,
+    /// Count the source and target of each shortest path as lying on it.
+    endpoints: bool,
+}
+
+/// Number type used for path counts and scores. `BigRational` gives exact
+/// answers no matter how many shortest paths there are; `f64` is the fast path
+/// for large graphs where a few ulps of rounding don't matter. Integer types
+/// are left out on purpose: normalizing would truncate.
+trait Score: Clone + PartialOrd + Num + FromPrimitive + Send {}
+
+impl Score for f32 {}
+impl Score for f64 {}
+impl Score for BigRational {}
+
+fn count<T: Score>(x: usize) -> T {
+    T::from_usize(x).expect("every score type can represent a vertex count")
+}
+
+/// Betweenness centrality using Brandes' algorithm
+/// (https://snap.stanford.edu/class/cs224w-readings/brandes01centrality.pdf).
+///
+/// We run one Dijkstra per source, counting the number of shortest paths
+/// (sigma) to every vertex and remembering all of their predecessors, so that
+/// ties are handled correctly. Then we walk back from the farthest vertex and
+/// accumulate each vertex's dependency on the source. This is O(nm + n² log n)
+/// instead of one A* search per pair of vertices, and the sources are spread
+/// across threads by `graphcore::paths`.
+///
+/// `edge_cost` gives the length of each edge, and lengths are summed in its
+/// return type: `|e| *e.weight()` on `f32` weights ties paths exactly when
+/// their `f32` lengths are equal, and `|_| 1` counts hops on an unweighted
+/// graph, such as the ones from `graphcore::generators`.
+///
+/// Path counts grow exponentially in lattice-like graphs, so the caller picks
+/// the score type, `Scores<_, BigRational>` or `Scores<_, f64>`.
+fn betweenness<T, N, E, Ty, D, F>(graph: &Graph<N, E, Ty>, edge_cost: F, options: Options) -> Scores<N, T>
+where
+    T: Score,
+    N: Clone + Sync,
+    E: Sync,
+    Ty: EdgeType + Sync,
+    D: Copy + PartialOrd + Zero,
+    F: Fn(EdgeReference<E>) -> D + Sync,
+{
+    let mut result = vec![T::zero(); graph.node_count()];
+    for_each_source(
+        graph,
+        edge_cost,
+        |sp: ShortestPaths<T, D>| sp.dependencies(options.endpoints),
+        |delta| {
+            for (x, d) in result.iter_mut().zip(delta) {
+                *x = x.clone() + d;
             }
-            for t in graph.node_indices() {
-                if x == t || s == t {
-                    continue
+        },
+    );
+    let (lo, scale) = rescale(&result, graph.is_directed(), options);
+    Scores::new(graph, result).map(|x| (x - lo.clone()) * scale.clone())
+}
+
+/// Affine map (x - lo) * scale that takes raw sums of dependencies to the
+/// requested normalization.
+fn rescale<T: Score>(result: &[T], directed: bool, options: Options) -> (T, T) {
+    let n = result.len();
+    let scale: T = match options.normalization {
+        // Undirected graphs have visited every pair from both ends.
+        Normalization::Raw if directed => T::one(),
+        Normalization::Raw => T::one() / count(2),
+        Normalization::Normalized => {
+            // Halving the undirected scores and halving the number of pairs
+            // cancel out, so both cases divide by the same count.
+            let pairs = if options.endpoints {
+                n * n.saturating_sub(1)
+            } else {
+                n.saturating_sub(1) * n.saturating_sub(2)
+            };
+            if pairs == 0 {
+                T::one()
+            } else {
+                T::one() / count(pairs)
+            }
+        }
+        Normalization::MinMax => {
+            let Some(first) = result.first().cloned() else {
+                return (T::zero(), T::one());
+            };
+            let (lo, hi) = result.iter().fold((first.clone(), first), |(lo, hi), x| {
+                (if *x < lo { x.clone() } else { lo }, if *x > hi { x.clone() } else { hi })
+            });
+            let scale = if hi == lo { T::zero() } else { T::one() / (hi - lo.clone()) };
+            return (lo, scale);
+        }
+    };
+    (T::zero(), scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_traits::ToPrimitive;
+
+    fn scores<Ty: EdgeType + Sync>(graph: &Graph<&str, f32, Ty>) -> Vec<BigRational> {
+        scores_with(graph, Options::default())
+    }
+
+    fn scores_with<Ty: EdgeType + Sync>(graph: &Graph<&str, f32, Ty>, options: Options) -> Vec<BigRational> {
+        let scores: Scores<_, BigRational> = betweenness(graph, |e| *e.weight(), options);
+        scores.iter().map(|e| e.score.clone()).collect()
+    }
+
+    fn ints(v: &[i64]) -> Vec<BigRational> {
+        v.iter().map(|&x| BigRational::from_i64(x).unwrap()).collect()
+    }
+
+    fn ratios(v: &[(i64, i64)]) -> Vec<BigRational> {
+        v.iter().map(|&(a, b)| BigRational::from_i64(a).unwrap() / BigRational::from_i64(b).unwrap()).collect()
+    }
+
+    #[test]
+    fn neo4j_weighted() {
+        // gds.betweenness.stream with relationshipWeightProperty: 'weight'
+        let expected = ints(&[0, 0, 8, 0, 6, 5, 0]);
+        assert_eq!(scores(&alice_gale()), expected);
+    }
+
+    #[test]
+    fn ties_split_credit() {
+        // Without weights Carol reaches Frank through Dan and Eve equally.
+        let expected = ints(&[0, 0, 8, 3, 3, 5, 0]);
+        assert_eq!(scores(&alice_gale().map(|_, &name| name, |_, _| 1.0)), expected);
+    }
+
+    #[test]
+    fn diamond() {
+        // s -> {a, b, c} -> t: each middle vertex carries a third of one path.
+        let mut graph: DiGraph<&str, f32> = Graph::new();
+        let s = graph.add_node("s");
+        let t = graph.add_node("t");
+        for name in ["a", "b", "c"] {
+            let x = graph.add_node(name);
+            graph.extend_with_edges([(s, x, 1.0), (x, t, 1.0)]);
+        }
+        let expected = ratios(&[(0, 1), (0, 1), (1, 3), (1, 3), (1, 3)]);
+        assert_eq!(scores(&graph), expected);
+    }
+
+    #[test]
+    fn ties_in_f32() {
+        // 0.1 + 0.2 == 0.3 in f32 but not once the weights are widened to
+        // f64, so both paths to t are shortest and a carries half of them.
+        assert_ne!(0.1f32 as f64 + 0.2f32 as f64, 0.3f32 as f64);
+        let mut graph: DiGraph<&str, f32> = Graph::new();
+        let s = graph.add_node("s");
+        let a = graph.add_node("a");
+        let t = graph.add_node("t");
+        graph.extend_with_edges([(s, a, 0.1), (a, t, 0.2), (s, t, 0.3)]);
+        assert_eq!(scores(&graph), ratios(&[(0, 1), (1, 2), (0, 1)]));
+        let sampling = Sampling { size: 3, seed: 1, pivots: Pivots::Uniform };
+        let approx = approximate_betweenness(&graph, |e| *e.weight(), sampling, Options::default());
+        assert_eq!(approx[a].score, 0.5);
+    }
+
+    #[test]
+    fn normalized_directed() {
+        // graphrs betweenness_centrality(&g, true, true): raw / (6 * 5).
+        let options = Options { normalization: Normalization::Normalized, ..Default::default() };
+        let expected = ratios(&[(0, 1), (0, 1), (8, 30), (0, 1), (6, 30), (5, 30), (0, 1)]);
+        assert_eq!(scores_with(&alice_gale(), options), expected);
+    }
+
+    #[test]
+    fn min_max() {
+        let options = Options { normalization: Normalization::MinMax, ..Default::default() };
+        let expected = ratios(&[(0, 1), (0, 1), (1, 1), (0, 1), (6, 8), (5, 8), (0, 1)]);
+        assert_eq!(scores_with(&alice_gale(), options), expected);
+    }
+
+    #[test]
+    fn endpoints() {
+        // Raw score plus the vertices each one reaches and is reached by.
+        let options = Options { endpoints: true, ..Default::default() };
+        let expected = ints(&[5, 5, 14, 5, 11, 11, 6]);
+        assert_eq!(scores_with(&alice_gale(), options), expected);
+
+        let options = Options { normalization: Normalization::Normalized, endpoints: true };
+        let expected = ratios(&[(5, 42), (5, 42), (14, 42), (5, 42), (11, 42), (11, 42), (6, 42)]);
+        assert_eq!(scores_with(&alice_gale(), options), expected);
+    }
+
+    #[test]
+    fn undirected_path() {
+        // a - b - c - d - e, each unordered pair counted once.
+        let mut graph: UnGraph<&str, f32> = Graph::new_undirected();
+        let v: Vec<_> = ["a", "b", "c", "d", "e"].map(|x| graph.add_node(x)).into();
+        graph.extend_with_edges(v.windows(2).map(|w| (w[0], w[1], 1.0)));
+        let expected = ints(&[0, 3, 4, 3, 0]);
+        assert_eq!(scores(&graph), expected);
+
+        // Normalized by (n-1)(n-2)/2 = 6.
+        let options = Options { normalization: Normalization::Normalized, ..Default::default() };
+        let expected = ratios(&[(0, 1), (1, 2), (2, 3), (1, 2), (0, 1)]);
+        assert_eq!(scores_with(&graph, options), expected);
+    }
+
+    #[test]
+    fn lattice_does_not_overflow() {
+        // A directed 18x18 grid has C(34, 17) > 2^31 shortest paths from one
+        // corner to the other, which used to overflow Rational32.
+        let k = 18;
+        let mut graph: DiGraph<&str, f32> = Graph::new();
+        let v: Vec<_> = (0..k * k).map(|_| graph.add_node("")).collect();
+        for i in 0..k {
+            for j in 0..k {
+                if i + 1 < k {
+                    graph.add_edge(v[i * k + j], v[(i + 1) * k + j], 1.0);
+                }
+                if j + 1 < k {
+                    graph.add_edge(v[i * k + j], v[i * k + j + 1], 1.0);
                 }
-                let result = astar(&graph, s, |finish| finish == t, |e| *e.weight(), |_| 0.0);
-                match result {
-                    Some((_, path)) => {
-                        if path.contains(&x) {
-                            paths_with_x += 1;
-                        }
-                        paths_total += 1;
-                    },
-                    None => ()
-                };       
             }
         }
-        result.insert(x, Rational32::new(paths_with_x, paths_total));
+        let exact = scores(&graph);
+        let fast: Scores<_, f64> = betweenness(&graph, |e| *e.weight(), Options::default());
+        for (v, x) in graph.node_indices().zip(exact) {
+            let x = x.to_f64().unwrap();
+            assert!((x - fast[v]).abs() <= 1e-9 * x.max(1.0));
+        }
+    }
+
+    #[test]
+    fn generated_graphs() {
+        // The generators have no edge weights, so count hops.
+        let graph = graphcore::generators::barabasi_albert(60, 2, 5);
+        let hops: Scores<_, BigRational> = betweenness(&graph, |_| 1u32, Options::default());
+        let unit = graph.map(|_, &n| n, |_, _| 1.0f32);
+        let weighted: Scores<_, BigRational> = betweenness(&unit, |e| *e.weight(), Options::default());
+        assert_eq!(hops, weighted);
+
+        let sampling = Sampling { size: 60, seed: 1, pivots: Pivots::Uniform };
+        let approx = approximate_betweenness(&graph, |_| 1u32, sampling, Options::default());
+        for v in graph.node_indices() {
+            let x = hops[v].to_f64().unwrap();
+            assert!((approx[v].score - x).abs() <= 1e-9 * x.max(1.0));
+        }
     }
-    result
 }
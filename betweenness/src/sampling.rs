@@ -0,0 +1,235 @@
+//! Approximate betweenness from a sample of source pivots, like the
+//! `samplingSize` parameter of gds.betweenness.stream.
+//!
+//! Brandes and Pich, "Centrality Estimation in Large Networks"
+//! (https://doi.org/10.1142/S0218127407018403).
+
+use std::fmt;
+
+use graphcore::paths::{for_each_of, ShortestPaths};
+use graphcore::scores::Scores;
+use num_traits::Zero;
+use petgraph::graph::EdgeReference;
+use petgraph::prelude::*;
+use petgraph::EdgeType;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{rescale, Options};
+
+/// How source pivots are chosen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pivots {
+    /// Every vertex is equally likely. Pivots are drawn without replacement,
+    /// so a sample of all n vertices gives the exact answer.
+    #[default]
+    Uniform,
+    /// Vertices are drawn with replacement in proportion to their degree, and
+    /// each pivot's dependencies are weighted by the inverse of its
+    /// probability so that the estimate stays unbiased.
+    Degree,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sampling {
+    /// Number of pivots. Must be at least one.
+    pub size: usize,
+    /// Same seed, same pivots, same scores.
+    pub seed: u64,
+    pub pivots: Pivots,
+}
+
+/// An approximate score and its standard error, both on the scale chosen by
+/// `Options::normalization`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub score: f64,
+    pub error: f64,
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.3} ± {:.3}", self.score, self.error)
+    }
+}
+
+/// Estimate betweenness by running the single-source part of Brandes'
+/// algorithm from a random sample of pivots and extrapolating to all n
+/// sources. The pivots are spread across threads like the exact version's
+/// sources, and their dependencies summed in the order they were drawn.
+///
+/// The error is the standard error of the extrapolated sum. Uniform sampling
+/// applies the finite population correction, so it falls to zero once every
+/// vertex is a pivot. With only one pivot there is nothing to estimate the
+/// spread from and the error is infinite.
+pub fn approximate_betweenness<N, E, Ty, D, F>(
+    graph: &Graph<N, E, Ty>,
+    edge_cost: F,
+    sampling: Sampling,
+    options: Options,
+) -> Scores<N, Estimate>
+where
+    N: Clone + Sync,
+    E: Sync,
+    Ty: EdgeType + Sync,
+    D: Copy + PartialOrd + Zero,
+    F: Fn(EdgeReference<E>) -> D + Sync,
+{
+    assert!(sampling.size > 0, "sample size must be positive");
+    let n = graph.node_count();
+    if n == 0 {
+        return Scores::new(graph, []);
+    }
+    let mut rng = ChaCha8Rng::seed_from_u64(sampling.seed);
+
+    // Each pivot with the inverse of its probability of being drawn.
+    let degree: Vec<usize> = graph
+        .node_indices()
+        .map(|v| graph.neighbors_undirected(v).count())
+        .collect();
+    let (pivots, fpc): (Vec<(NodeIndex, f64)>, f64) = match WeightedIndex::new(&degree) {
+        Ok(dist) if sampling.pivots == Pivots::Degree => {
+            let total = degree.iter().sum::<usize>() as f64;
+            let pivots = (0..sampling.size)
+                .map(|_| {
+                    let s = dist.sample(&mut rng);
+                    (NodeIndex::new(s), total / degree[s] as f64)
+                })
+                .collect();
+            (pivots, 1.0)
+        }
+        // A graph without edges has no dependencies to weight by, so we may
+        // as well sample uniformly.
+        _ => {
+            let k = sampling.size.min(n);
+            let pivots = rand::seq::index::sample(&mut rng, n, k)
+                .into_iter()
+                .map(|s| (NodeIndex::new(s), n as f64))
+                .collect();
+            let fpc = if n > 1 { (n - k) as f64 / (n - 1) as f64 } else { 0.0 };
+            (pivots, fpc)
+        }
+    };
+
+    // Running sums of each pivot's weighted dependencies and their squares.
+    let mut sum = vec![0.0; n];
+    let mut sum_sq = vec![0.0; n];
+    let sources: Vec<NodeIndex> = pivots.iter().map(|&(s, _)| s).collect();
+    let mut weights = pivots.iter().map(|&(_, weight)| weight);
+    for_each_of(
+        graph,
+        &sources,
+        edge_cost,
+        |sp: ShortestPaths<f64, D>| sp.dependencies(options.endpoints),
+        |delta| {
+            let weight = weights.next().unwrap();
+            for (v, d) in delta.into_iter().enumerate() {
+                let x = d * weight;
+                sum[v] += x;
+                sum_sq[v] += x * x;
+            }
+        },
+    );
+
+    let k = pivots.len() as f64;
+    let score: Vec<f64> = sum.iter().map(|x| x / k).collect();
+    let error: Vec<f64> = sum
+        .iter()
+        .zip(&sum_sq)
+        .map(|(x, x2)| {
+            if fpc == 0.0 {
+                0.0
+            } else if k < 2.0 {
+                f64::INFINITY
+            } else {
+                let variance = ((x2 - x * x / k) / (k - 1.0)).max(0.0);
+                (variance / k * fpc).sqrt()
+            }
+        })
+        .collect();
+
+    let (lo, scale) = rescale(&score, graph.is_directed(), options);
+    let estimates = score
+        .into_iter()
+        .zip(error)
+        .map(|(x, e)| Estimate { score: (x - lo) * scale, error: e * scale.abs() });
+    Scores::new(graph, estimates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alice_gale, betweenness, Normalization};
+
+    /// Largest absolute difference from the exact scores.
+    fn max_error(graph: &DiGraph<&str, f32>, sampling: Sampling) -> f64 {
+        let exact: Scores<_, f64> = betweenness(graph, |e| *e.weight(), Options::default());
+        let approx = approximate_betweenness(graph, |e| *e.weight(), sampling, Options::default());
+        graph
+            .node_indices()
+            .map(|v| (exact[v] - approx[v].score).abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn full_sample_is_exact() {
+        // samplingSize: 7 on a graph of 7 vertices, as in the Neo4j example.
+        let graph = alice_gale();
+        let sampling = Sampling { size: 7, seed: 42, pivots: Pivots::Uniform };
+        let approx = approximate_betweenness(&graph, |e| *e.weight(), sampling, Options::default());
+        let expected = [0.0, 0.0, 8.0, 0.0, 6.0, 5.0, 0.0];
+        for (v, x) in graph.node_indices().zip(expected) {
+            assert_eq!(approx[v], Estimate { score: x, error: 0.0 });
+        }
+    }
+
+    #[test]
+    fn same_seed_same_scores() {
+        let graph = alice_gale();
+        for pivots in [Pivots::Uniform, Pivots::Degree] {
+            let sampling = Sampling { size: 3, seed: 7, pivots };
+            let options = Options { normalization: Normalization::Normalized, ..Default::default() };
+            assert_eq!(
+                approximate_betweenness(&graph, |e| *e.weight(), sampling, options),
+                approximate_betweenness(&graph, |e| *e.weight(), sampling, options),
+            );
+        }
+    }
+
+    #[test]
+    fn uniform_converges() {
+        // Average the worst error over many seeds for growing samples.
+        let graph = alice_gale();
+        let mean_error = |size| {
+            (0..50)
+                .map(|seed| max_error(&graph, Sampling { size, seed, pivots: Pivots::Uniform }))
+                .sum::<f64>()
+                / 50.0
+        };
+        let errors: Vec<f64> = (1..=7).map(mean_error).collect();
+        assert!(errors.windows(2).all(|w| w[1] <= w[0]), "{errors:?}");
+        assert_eq!(errors[6], 0.0);
+    }
+
+    #[test]
+    fn degree_converges() {
+        let graph = alice_gale();
+        let small = max_error(&graph, Sampling { size: 7, seed: 1, pivots: Pivots::Degree });
+        let large = max_error(&graph, Sampling { size: 7000, seed: 1, pivots: Pivots::Degree });
+        assert!(large < small);
+        assert!(large < 0.5, "{large}");
+    }
+
+    #[test]
+    fn error_covers_exact_value() {
+        let graph = alice_gale();
+        let exact: Scores<_, f64> = betweenness(&graph, |e| *e.weight(), Options::default());
+        let sampling = Sampling { size: 1000, seed: 3, pivots: Pivots::Degree };
+        let approx = approximate_betweenness(&graph, |e| *e.weight(), sampling, Options::default());
+        for v in graph.node_indices() {
+            let Estimate { score, error } = approx[v];
+            assert!((score - exact[v]).abs() <= 4.0 * error + 1e-9, "{v:?}: {score} ± {error}");
+        }
+    }
+}
@@ -1,30 +1,157 @@
-use petgraph::algo::dijkstra;
+use std::env;
+
+use graphcore::connectivity::{Connectivity, Measure};
+use graphcore::distance::harmonic;
+use graphcore::examples::alice_gale;
+use graphcore::paths::{for_each_source, path_summary, ShortestPaths};
+use graphcore::scores::{Format, Scores};
+use petgraph::graph::EdgeReference;
 use petgraph::prelude::*;
-use petgraph::visit::NodeRef;
-use petgraph::Graph;
+use petgraph::{EdgeType, Graph};
+
+const USAGE: &str = "Usage: closeness [table|csv|json]";
 
 fn main() {
-    let mut graph: Graph<(), (), Undirected> = Graph::new_undirected();
-    let a = graph.add_node(());
-    let b = graph.add_node(());
-    let c = graph.add_node(());
-    let d = graph.add_node(());
-    let e = graph.add_node(());
-    graph.extend_with_edges(&[(a, b), (b, c), (c, d), (d, e)]);
+    let format = Format::from_arg(env::args().nth(1).as_deref(), USAGE);
+
+    let mut graph: Graph<&str, (), Undirected> = Graph::new_undirected();
+    let a = graph.add_node("a");
+    let b = graph.add_node("b");
+    let c = graph.add_node("c");
+    let d = graph.add_node("d");
+    let e = graph.add_node("e");
+    graph.extend_with_edges([(a, b), (b, c), (c, d), (d, e)]);
     let graph = graph;
-    closeness(&graph);
+    print!("{}", closeness(&graph, |_| 1.0, false).render(format, "closeness"));
+
+    // Eccentricities would be infinite if some vertex couldn't reach another.
+    if format == Format::Table {
+        let summary = path_summary(&graph, |_| 1.0);
+        println!("Eccentricities are {:?}.", summary.eccentricity);
+        println!("Average path length is {}.", summary.average_reachable_path_length);
+        let center: Vec<_> = summary.center().into_iter().map(|v| graph[v]).collect();
+        let periphery: Vec<_> = summary.periphery().into_iter().map(|v| graph[v]).collect();
+        println!("Diameter {}, radius {}.", summary.diameter(), summary.radius());
+        println!("Center {center:?}, periphery {periphery:?}.");
+    }
+
+    // gds.closeness.stream on the graph from rsgraph. GDS ignores the weights
+    // and follows the relationships in their natural direction.
+    let graph = alice_gale();
+    let connectivity = Connectivity::of(&graph);
+    connectivity.warn(Measure::Closeness);
+    println!();
+    print!("{}", closeness(&graph, |_| 1.0, false).render(format, "GDS"));
+    println!();
+    print!("{}", closeness(&graph, |_| 1.0, true).render(format, "Wasserman-Faust"));
+    println!();
+    print!("{}", closeness(&graph, |e| *e.weight(), false).render(format, "weighted"));
+
+    // Harmonic centrality doesn't mind that Gale can't reach anyone.
+    connectivity.warn(Measure::Harmonic);
+    let mut harmonic = harmonic(&graph, |_| 1.0, true);
+    harmonic.sort_descending();
+    println!();
+    print!("{}", harmonic.render(format, "harmonic"));
+}
+
+/// Closeness centrality: the number of vertices u can reach divided by the
+/// sum of the distances to them. Directed graphs follow outgoing edges, and
+/// `edge_cost` gives the length of each edge (`|_| 1.0` for hop counts).
+///
+/// When u reaches everyone this is the familiar (n - 1) / sum of distances.
+/// When it doesn't, a vertex that reaches only one close neighbor would look
+/// very central, so `wasserman_faust` multiplies by the fraction of the graph
+/// u can reach, r / (n - 1). This matches `useWassermanFaust` in Neo4j GDS.
+/// Vertices that reach nobody score zero. `Connectivity::diagnose` says when
+/// leaving out the correction would mislead.
+fn closeness<N, E, Ty, F>(
+    graph: &Graph<N, E, Ty>,
+    edge_cost: F,
+    wasserman_faust: bool,
+) -> Scores<N>
+where
+    N: Clone + Sync,
+    E: Sync,
+    Ty: EdgeType + Sync,
+    F: Fn(EdgeReference<E>) -> f64 + Sync,
+{
+    let n = graph.node_count() as f64;
+    let mut result = Vec::with_capacity(graph.node_count());
+    for_each_source(
+        graph,
+        edge_cost,
+        |sp: ShortestPaths<f64>| (sp.reached() as f64, sp.total_distance()),
+        |(reached, distances)| {
+            let mut closeness = if distances > 0.0 { reached / distances } else { 0.0 };
+            if wasserman_faust && n > 1.0 {
+                closeness *= reached / (n - 1.0);
+            }
+            result.push(closeness);
+        },
+    );
+    Scores::new(graph, result)
 }
 
-fn closeness<N, E>(graph: &Graph<N, E, Undirected>) {
-    for u in graph.node_indices() {
-        let delta = dijkstra(&graph, u.id(), None, |_| 1);
-        let n = graph.node_count() as f64;
-        let distances = delta.values().cloned().sum::<i32>() as f64;
-        let closeness = (n - 1.0) / distances;
-        println!(
-            "Closeness score for vertex {} is {}.",
-            u.index(),
-            closeness
-        );
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphcore::examples::two_components;
+
+    fn scores<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, result: Scores<N>) -> Vec<f64> {
+        graph.node_indices().map(|u| result[u]).collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn undirected_path() {
+        let mut graph: UnGraph<(), ()> = Graph::new_undirected();
+        let v: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        graph.extend_with_edges(v.windows(2).map(|w| (w[0], w[1])));
+        let expected = [0.4, 4.0 / 7.0, 4.0 / 6.0, 4.0 / 7.0, 0.4];
+        assert_close(&scores(&graph, closeness(&graph, |_| 1.0, false)), &expected);
+        // Everyone reaches everyone, so the correction changes nothing.
+        assert_close(&scores(&graph, closeness(&graph, |_| 1.0, true)), &expected);
+    }
+
+    #[test]
+    fn gds_closeness_stream() {
+        // CALL gds.closeness.stream('myGraph')
+        let graph = alice_gale();
+        let expected = [5.0 / 12.0, 5.0 / 12.0, 4.0 / 7.0, 2.0 / 3.0, 2.0 / 3.0, 1.0, 0.0];
+        assert_close(&scores(&graph, closeness(&graph, |_| 1.0, false)), &expected);
+    }
+
+    #[test]
+    fn gds_wasserman_faust() {
+        // CALL gds.closeness.stream('myGraph', {useWassermanFaust: true})
+        let graph = alice_gale();
+        let expected = [25.0 / 72.0, 25.0 / 72.0, 8.0 / 21.0, 2.0 / 9.0, 2.0 / 9.0, 1.0 / 6.0, 0.0];
+        assert_close(&scores(&graph, closeness(&graph, |_| 1.0, true)), &expected);
+    }
+
+    #[test]
+    fn weighted() {
+        // Carol reaches Frank through Eve for 1.8 instead of through Dan for 2.
+        let graph = alice_gale();
+        let result = scores(&graph, closeness(&graph, |e| *e.weight(), false));
+        assert_close(&result[2..4], &[4.0 / (1.0 + 1.3 + 1.8 + 2.8), 2.0 / 3.0]);
+    }
+
+    #[test]
+    fn harmonic_vs_closeness() {
+        // Closeness ranks d above a because d reaches its only neighbor in
+        // one hop. Harmonic centrality credits a for reaching more vertices.
+        let graph = two_components();
+        let closeness = scores(&graph, closeness(&graph, |_| 1.0, false));
+        let harmonic = scores(&graph, harmonic(&graph, |_| 1.0, false));
+        assert!(closeness[3] > closeness[0]);
+        assert!(harmonic[3] < harmonic[0]);
     }
 }
@@ -0,0 +1,17 @@
+CREATE
+  (A:PERSON {name:"A"}),
+  (B:PERSON {name:"B"}),
+  (C:PERSON {name:"C"}),
+  (D:PERSON {name:"D"}),
+  (a:PERSON {name:"a"}),
+  (b:PERSON {name:"b"}),
+  (c:PERSON {name:"c"}),
+  (d:PERSON {name:"d"}),
+  (A)-[:CONN]->(B),
+  (B)-[:CONN]->(C),
+  (C)-[:CONN]->(D),
+  (A)-[:CONN]->(D),
+  (a)-[:CONN]->(b),
+  (b)-[:CONN]->(c),
+  (c)-[:CONN]->(d),
+  (c)-[:CONN]->(a);
@@ -0,0 +1,51 @@
+CREATE
+  (Frank:PERSON {name:"Frank"}),
+  (Kim:PERSON {name:"Kim"}),
+  (David:PERSON {name:"David"}),
+  (Sven:PERSON {name:"Sven"}),
+  (Adin:PERSON {name:"Adin"}),
+  (Maria:PERSON {name:"Maria"}),
+  (Jose:PERSON {name:"Jose"}),
+  (Jasmine:PERSON {name:"Jasmine"}),
+  (Ivan:PERSON {name:"Ivan"}),
+  (Jean:PERSON {name:"Jean"}),
+  (Felix:PERSON {name:"Felix"}),
+  (Phil:PERSON {name:"Phil"}),
+  (Frank)-[:CONN]->(Kim),
+  (Kim)-[:CONN]->(Frank),
+  (Frank)-[:CONN]->(David),
+  (David)-[:CONN]->(Frank),
+  (David)-[:CONN]->(Sven),
+  (Sven)-[:CONN]->(David),
+  (David)-[:CONN]->(Adin),
+  (Adin)-[:CONN]->(David),
+  (Adin)-[:CONN]->(Maria),
+  (Maria)-[:CONN]->(Adin),
+  (Adin)-[:CONN]->(Jose),
+  (Jose)-[:CONN]->(Adin),
+  (Adin)-[:CONN]->(Kim),
+  (Kim)-[:CONN]->(Adin),
+  (Maria)-[:CONN]->(Jose),
+  (Jose)-[:CONN]->(Maria),
+  (Jose)-[:CONN]->(Kim),
+  (Kim)-[:CONN]->(Jose),
+  (Jasmine)-[:CONN]->(Jose),
+  (Jose)-[:CONN]->(Jasmine),
+  (Jasmine)-[:CONN]->(Kim),
+  (Kim)-[:CONN]->(Jasmine),
+  (Ivan)-[:CONN]->(Adin),
+  (Adin)-[:CONN]->(Ivan),
+  (Jean)-[:CONN]->(Ivan),
+  (Ivan)-[:CONN]->(Jean),
+  (Felix)-[:CONN]->(Ivan),
+  (Ivan)-[:CONN]->(Felix),
+  (Kim)-[:CONN]->(Felix),
+  (Felix)-[:CONN]->(Kim),
+  (Kim)-[:CONN]->(David),
+  (David)-[:CONN]->(Kim),
+  (Kim)-[:CONN]->(Jean),
+  (Jean)-[:CONN]->(Kim),
+  (Sven)-[:CONN]->(Adin),
+  (Adin)-[:CONN]->(Sven),
+  (Phil)-[:CONN]->(Adin),
+  (Adin)-[:CONN]->(Phil);
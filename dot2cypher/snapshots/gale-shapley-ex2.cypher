@@ -0,0 +1,20 @@
+CREATE
+  (`[4, 3, 2, 1]`:PERSON {name:"[4, 3, 2, 1]", color:"blue"}),
+  (`[4, 2, 3, 1]`:PERSON {name:"[4, 2, 3, 1]"}),
+  (`[4, 1, 2, 3]`:PERSON {name:"[4, 1, 2, 3]"}),
+  (`[4, 3, 1, 2]`:PERSON {name:"[4, 3, 1, 2]", color:"blue"}),
+  (`[4, 2, 1, 3]`:PERSON {name:"[4, 2, 1, 3]", color:"blue"}),
+  (`[4, 1, 3, 2]`:PERSON {name:"[4, 1, 3, 2]"}),
+  (`[2, 4, 1, 3]`:PERSON {name:"[2, 4, 1, 3]", color:"blue"}),
+  (`[1, 2, 4, 3]`:PERSON {name:"[1, 2, 4, 3]"}),
+  (`[2, 1, 4, 3]`:PERSON {name:"[2, 1, 4, 3]"}),
+  (`[3, 4, 1, 2]`:PERSON {name:"[3, 4, 1, 2]", color:"blue"}),
+  (`[4, 3, 2, 1]`)-[:CONN]->(`[4, 2, 3, 1]`),
+  (`[4, 3, 2, 1]`)-[:CONN]->(`[4, 1, 2, 3]`),
+  (`[4, 3, 2, 1]`)-[:CONN]->(`[4, 3, 1, 2]`),
+  (`[4, 3, 1, 2]`)-[:CONN]->(`[4, 2, 1, 3]`),
+  (`[4, 3, 1, 2]`)-[:CONN]->(`[4, 1, 3, 2]`),
+  (`[4, 2, 1, 3]`)-[:CONN]->(`[2, 4, 1, 3]`),
+  (`[4, 2, 1, 3]`)-[:CONN]->(`[1, 2, 4, 3]`),
+  (`[2, 4, 1, 3]`)-[:CONN]->(`[2, 1, 4, 3]`),
+  (`[2, 4, 1, 3]`)-[:CONN]->(`[3, 4, 1, 2]`);
@@ -0,0 +1,9 @@
+CREATE
+  (hat:PERSON {name:"hat"}),
+  (socks:PERSON {name:"socks"}),
+  (shoes:PERSON {name:"shoes"}),
+  (pants:PERSON {name:"pants"}),
+  (shirt:PERSON {name:"shirt"}),
+  (coat:PERSON {name:"coat"}),
+  (socks)-[:CONN]->(shoes),
+  (shirt)-[:CONN]->(coat);
@@ -0,0 +1,39 @@
+CREATE
+  (start:PERSON {name:"start"}),
+  (forest:PERSON {name:"forest"}),
+  (mountains:PERSON {name:"mountains"}),
+  (sea:PERSON {name:"sea"}),
+  (city:PERSON {name:"city"}),
+  (desert:PERSON {name:"desert"}),
+  (cave:PERSON {name:"cave"}),
+  (glacier:PERSON {name:"glacier"}),
+  (inferno:PERSON {name:"inferno"}),
+  (beach:PERSON {name:"beach"}),
+  (castle:PERSON {name:"castle"}),
+  (treasure:PERSON {name:"treasure"}),
+  (start)-[:CONN]->(forest),
+  (forest)-[:CONN]->(start),
+  (start)-[:CONN]->(mountains),
+  (mountains)-[:CONN]->(start),
+  (start)-[:CONN]->(sea),
+  (sea)-[:CONN]->(start),
+  (start)-[:CONN]->(city),
+  (city)-[:CONN]->(start),
+  (forest)-[:CONN]->(mountains),
+  (mountains)-[:CONN]->(forest),
+  (forest)-[:CONN]->(desert),
+  (desert)-[:CONN]->(forest),
+  (forest)-[:CONN]->(cave),
+  (cave)-[:CONN]->(forest),
+  (mountains)-[:CONN]->(glacier),
+  (glacier)-[:CONN]->(mountains),
+  (cave)-[:CONN]->(inferno),
+  (inferno)-[:CONN]->(cave),
+  (sea)-[:CONN]->(beach),
+  (beach)-[:CONN]->(sea),
+  (beach)-[:CONN]->(city),
+  (city)-[:CONN]->(beach),
+  (city)-[:CONN]->(castle),
+  (castle)-[:CONN]->(city),
+  (castle)-[:CONN]->(treasure),
+  (treasure)-[:CONN]->(castle);
@@ -0,0 +1,8 @@
+CREATE
+  (Paris:PERSON {name:"Paris"}),
+  (Brussels:PERSON {name:"Brussels"}),
+  (Hague:PERSON {name:"Hague"}),
+  (Paris)-[:CONN {label:350}]->(Brussels),
+  (Brussels)-[:CONN {label:350}]->(Paris),
+  (Brussels)-[:CONN {label:180}]->(Hague),
+  (Hague)-[:CONN {label:180}]->(Brussels);
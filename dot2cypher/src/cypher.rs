@@ -0,0 +1,205 @@
+//! Cypher for a DOT graph. The output only depends on the input: nodes come
+//! in order of first mention and relationships in the order of their edges.
+
+use graphcore::dot::{Attributes, DotGraph};
+use itertools::Itertools;
+
+/// How to write the graph.
+pub struct Options {
+    /// The label on every node.
+    pub label: String,
+    /// The type of every relationship.
+    pub relationship: String,
+    /// Write an undirected edge as one relationship, the way round it's
+    /// written in the DOT file, instead of one each way.
+    pub once: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { label: "PERSON".into(), relationship: "CONN".into(), once: false }
+    }
+}
+
+/// A single CREATE statement for the whole graph. Every node gets its DOT ID
+/// as its `name` property, which wins over a DOT `name` attribute, and as
+/// its variable.
+pub fn cypher(dot: &DotGraph, options: &Options) -> String {
+    let label = identifier(&options.label);
+    let mut patterns = Vec::new();
+
+    for node in &dot.nodes {
+        let name = [("name", node.name.as_str())].into_iter();
+        let attributes = node.attributes.iter().filter(|(k, _)| *k != "name").map(|(k, v)| (k.as_str(), v.as_str()));
+        patterns.push(format!("({}:{label} {})", identifier(&node.name), properties(name.chain(attributes))));
+    }
+    for edge in &dot.edges {
+        let u = identifier(&dot.nodes[edge.tail].name);
+        let v = identifier(&dot.nodes[edge.head].name);
+        let relationship = relationship(&identifier(&options.relationship), &edge.attributes);
+        patterns.push(format!("({u})-{relationship}->({v})"));
+        if !dot.directed && !options.once {
+            patterns.push(format!("({v})-{relationship}->({u})"));
+        }
+    }
+
+    if patterns.is_empty() {
+        return String::new();
+    }
+    format!("CREATE\n  {};\n", patterns.iter().join(",\n  "))
+}
+
+fn relationship(kind: &str, attributes: &Attributes) -> String {
+    if attributes.is_empty() {
+        format!("[:{kind}]")
+    } else {
+        format!("[:{kind} {}]", properties(attributes.iter().map(|(k, v)| (k.as_str(), v.as_str()))))
+    }
+}
+
+fn properties<'a>(pairs: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+    format!("{{{}}}", pairs.map(|(key, value)| format!("{}:{}", identifier(key), literal(value))).join(", "))
+}
+
+/// Words Cypher reserves, which have to be quoted to be used as names.
+const RESERVED: &[&str] = &[
+    "ALL", "AND", "AS", "ASC", "ASCENDING", "BY", "CALL", "CASE", "CONSTRAINT", "CONTAINS", "CREATE", "DELETE",
+    "DESC", "DESCENDING", "DETACH", "DISTINCT", "DO", "DROP", "ELSE", "END", "ENDS", "EXISTS", "FALSE", "FOR",
+    "FOREACH", "FROM", "IN", "INDEX", "IS", "LIMIT", "LOAD", "MANDATORY", "MATCH", "MERGE", "NOT", "NULL", "OF",
+    "ON", "OPTIONAL", "OR", "ORDER", "REMOVE", "REQUIRE", "RETURN", "SCALAR", "SET", "SKIP", "STARTS", "THEN",
+    "TRUE", "UNION", "UNIQUE", "UNWIND", "WHEN", "WHERE", "WITH", "XOR", "YIELD",
+];
+
+/// A name as a Cypher variable, label, type or property key: as it is if
+/// it's a plain ASCII identifier and not a reserved word, and in backticks
+/// otherwise.
+fn identifier(name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !RESERVED.iter().any(|word| word.eq_ignore_ascii_case(name));
+    if plain {
+        name.to_string()
+    } else {
+        format!("`{}`", name.replace('`', "``"))
+    }
+}
+
+/// A DOT attribute value as a Cypher literal. Integers, floats and booleans
+/// keep their type, and anything else is a string. So is a number with a
+/// leading zero, like `007`, which would lose it.
+fn literal(value: &str) -> String {
+    let digits = value.trim_start_matches(['-', '+']);
+    if digits.starts_with('0') && digits[1..].starts_with(|c: char| c.is_ascii_digit()) {
+        return string(value);
+    }
+    if let Ok(n) = value.parse::<i64>() {
+        return n.to_string();
+    }
+    let numeric = value.chars().any(|c| c.is_ascii_digit());
+    match value.parse::<f64>() {
+        Ok(x) if numeric && x.is_finite() => format!("{x:?}"),
+        _ if value == "true" || value == "false" => value.to_string(),
+        _ => string(value),
+    }
+}
+
+/// A double-quoted Cypher string.
+fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphcore::dot::parse;
+
+    #[test]
+    fn typed_literals() {
+        let cases = [
+            ("350", "350"), ("-2", "-2"), ("0.5", "0.5"), (".5", "0.5"), ("1e3", "1000.0"), ("true", "true"),
+            ("LR", "\"LR\""), ("inf", "\"inf\""), ("NaN", "\"NaN\""), ("", "\"\""),
+            ("0", "0"), ("-0.5", "-0.5"), ("007", "\"007\""), ("-01", "\"-01\""), ("00.5", "\"00.5\""),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(literal(value), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(string(r#"say "hi" \ bye"#), r#""say \"hi\" \\ bye""#);
+        assert_eq!(string("two\nlines\u{7}"), r#""two\nlines\u0007""#);
+        assert_eq!(identifier("Paris"), "Paris");
+        assert_eq!(identifier("match"), "`match`");
+        assert_eq!(identifier("2nd"), "`2nd`");
+        assert_eq!(identifier("[4, 3, 2, 1]"), "`[4, 3, 2, 1]`");
+        assert_eq!(identifier("back`tick"), "`back``tick`");
+        assert_eq!(identifier("café"), "`café`");
+    }
+
+    #[test]
+    fn awkward_names() {
+        let source = r#"
+            digraph {
+                "O'Brien \"Bob\"" [note="a\b"];
+                "O'Brien \"Bob\"" -> return [label="line\nbreak", "odd key"=2];
+                return -> "7up";
+            }
+        "#;
+        let expected = r#"CREATE
+  (`O'Brien "Bob"`:PERSON {name:"O'Brien \"Bob\"", note:"a\\b"}),
+  (`return`:PERSON {name:"return"}),
+  (`7up`:PERSON {name:"7up"}),
+  (`O'Brien "Bob"`)-[:CONN {label:"line\\nbreak", `odd key`:2}]->(`return`),
+  (`return`)-[:CONN]->(`7up`);
+"#;
+        assert_eq!(cypher(&parse(source).unwrap(), &Options::default()), expected);
+    }
+
+    #[test]
+    fn directions() {
+        let options = Options { label: "Task".into(), relationship: "BEFORE".into(), once: false };
+        let dag = parse(include_str!("../../dag.dot")).unwrap();
+        let out = cypher(&dag, &options);
+        assert_eq!(out.matches("-[:BEFORE]->").count(), 8);
+        assert!(out.contains("(c)-[:BEFORE]->(a)") && !out.contains("(a)-[:BEFORE]->(c)"));
+
+        let kingdom = parse(include_str!("../../kingdom.dot")).unwrap();
+        assert_eq!(cypher(&kingdom, &options).matches("-[:BEFORE]->").count(), 26);
+        let once = Options { once: true, ..options };
+        let out = cypher(&kingdom, &once);
+        assert_eq!(out.matches("-[:BEFORE]->").count(), 13);
+        assert!(out.contains("(castle)-[:BEFORE]->(treasure)"));
+    }
+
+    /// The output for every DOT file in the repository, with the default
+    /// options, is kept in `snapshots/`.
+    #[test]
+    fn snapshots() {
+        let files = [
+            (include_str!("../../dag.dot"), include_str!("../snapshots/dag.cypher")),
+            (include_str!("../../friends.dot"), include_str!("../snapshots/friends.cypher")),
+            (include_str!("../../gale-shapley-ex2.dot"), include_str!("../snapshots/gale-shapley-ex2.cypher")),
+            (include_str!("../../get-dressed.dot"), include_str!("../snapshots/get-dressed.cypher")),
+            (include_str!("../../kingdom.dot"), include_str!("../snapshots/kingdom.cypher")),
+            (include_str!("../../paris-brussels-hague.dot"), include_str!("../snapshots/paris-brussels-hague.cypher")),
+        ];
+        for (source, expected) in files {
+            assert_eq!(cypher(&parse(source).unwrap(), &Options::default()), expected);
+        }
+    }
+}
@@ -1,43 +1,47 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-use regex::Regex;
 use std::env;
-use std::collections::HashSet;
-use itertools::Itertools;
+use std::fs;
+use std::process;
+
+use graphcore::dot::parse;
+
+mod cypher;
+
+use cypher::{cypher, Options};
+
+const USAGE: &str = "Usage: dot2cypher [--label PERSON] [--type CONN] [--once] graph.dot";
 
 fn main() {
-    let re = Regex::new(r"(?<u>\w+)\s?--\s?(?<v>\w+);").unwrap();
-
-    let args: Vec<String> = env::args().collect();
-    
-    let mut vertices = HashSet::new();
-    let mut edges = HashSet::new();
-
-    // File hosts.txt must exist in the current path
-    if let Ok(lines) = read_lines(&args[1]) {
-        // Consumes the iterator, returns an (Optional) String
-        for line in lines.flatten() {
-            let Some(caps) = re.captures(&line) else {
-                continue;
-            };
-            vertices.insert(format!("({}:PERSON {{name:\"{}\"}})", &caps["u"], &caps["u"]));
-            vertices.insert(format!("({}:PERSON {{name:\"{}\"}})", &caps["v"], &caps["v"]));
-            edges.insert(format!("({})-[:CONN]->({})", &caps["u"], &caps["v"]));
-            edges.insert(format!("({})-[:CONN]->({})", &caps["v"], &caps["u"]));
-
-            //println!("Edge::new(\"{}\", \"{}\"),", &caps["u"], &caps["v"]);
+    let mut options = Options::default();
+    let mut path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--label" => options.label = name(args.next()),
+            "--type" => options.relationship = name(args.next()),
+            "--once" => options.once = true,
+            _ if arg.starts_with("--") || path.is_some() => usage(),
+            _ => path = Some(arg),
         }
     }
+    let path = path.unwrap_or_else(|| usage());
+
+    let source = fs::read_to_string(&path).unwrap_or_else(|e| {
+        eprintln!("{path}: {e}");
+        process::exit(1);
+    });
+    let dot = parse(&source).unwrap_or_else(|e| {
+        eprintln!("{path}:{e}");
+        process::exit(1);
+    });
+    print!("{}", cypher(&dot, &options));
+}
 
-    println!("CREATE\n  {},", vertices.iter().join(",\n  "));
-    println!("  {};", edges.iter().join(",\n  "));
+/// The value of `--label` or `--type`. Cypher has no empty names.
+fn name(arg: Option<String>) -> String {
+    arg.filter(|name| !name.is_empty()).unwrap_or_else(|| usage())
 }
 
-// The output is wrapped in a Result to allow matching on errors.
-// Returns an Iterator to the Reader of the lines of the file.
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(2);
 }
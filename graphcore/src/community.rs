@@ -0,0 +1,408 @@
+//! Community detection: edge betweenness, modularity, the divisive
+//! Girvan–Newman algorithm and Louvain, plus a DOT writer that colors the
+//! communities.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use petgraph::dot::{Config, Dot};
+use petgraph::graph::{EdgeIndex, EdgeReference, Graph, NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+
+use crate::connectivity::weak_components;
+use crate::paths::{for_each_source, ShortestPaths};
+use crate::scores::Scores;
+
+/// A community for every vertex, indexed by `NodeIndex::index()`.
+/// Communities are numbered from zero in order of their first vertex.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Partition {
+    pub community: Vec<usize>,
+    pub modularity: f64,
+}
+
+impl Partition {
+    /// Number of communities.
+    pub fn len(&self) -> usize {
+        self.community.iter().max().map_or(0, |c| c + 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.community.is_empty()
+    }
+
+    /// The community of every vertex, labelled with the vertex weights.
+    pub fn scores<N: Clone, E, Ty: EdgeType>(&self, graph: &Graph<N, E, Ty>) -> Scores<N, usize> {
+        Scores::new(graph, self.community.iter().copied())
+    }
+}
+
+/// The result of `girvan_newman`.
+#[derive(Clone, Debug)]
+pub struct GirvanNewman {
+    /// The connected components before any edge is removed, and then after
+    /// every removal that splits a component.
+    pub levels: Vec<Partition>,
+    /// Every edge of the original graph in the order it was removed.
+    pub removed: Vec<EdgeIndex>,
+    /// The level with the highest modularity.
+    pub best: usize,
+}
+
+/// Raw edge betweenness: the number of shortest paths through each edge,
+/// indexed by `EdgeIndex::index()`, with tied paths sharing the credit.
+/// Undirected graphs count each pair of vertices once. Parallel edges aren't
+/// told apart; the first of them gets all the credit.
+pub fn edge_betweenness<N, E, Ty, F>(graph: &Graph<N, E, Ty>, edge_cost: F) -> Vec<f64>
+where
+    N: Sync,
+    E: Sync,
+    Ty: EdgeType + Sync,
+    F: Fn(EdgeReference<E>) -> f64 + Sync,
+{
+    let mut result = vec![0.0; graph.edge_count()];
+    for_each_source(
+        graph,
+        edge_cost,
+        |sp: ShortestPaths<f64>| sp.edge_dependencies(),
+        |shares| {
+            for (v, w, share) in shares {
+                let edge = graph.find_edge(v, w).expect("predecessors are joined by an edge");
+                result[edge.index()] += share;
+            }
+        },
+    );
+    if !graph.is_directed() {
+        result.iter_mut().for_each(|x| *x /= 2.0);
+    }
+    result
+}
+
+/// Newman's modularity of an undirected graph split into `community`: the
+/// fraction of edge weight inside communities minus what we'd expect if the
+/// edges were rewired at random keeping every degree. A `resolution` above
+/// one penalizes large communities more, below one less.
+pub fn modularity<N, E, F>(graph: &UnGraph<N, E>, edge_weight: F, community: &[usize], resolution: f64) -> f64
+where
+    F: Fn(EdgeReference<E>) -> f64,
+{
+    let k = community.iter().max().map_or(0, |c| c + 1);
+    let mut inside = vec![0.0; k];
+    let mut degree = vec![0.0; k];
+    let mut m = 0.0;
+    for edge in graph.edge_references() {
+        let w = edge_weight(edge);
+        let (a, b) = (community[edge.source().index()], community[edge.target().index()]);
+        if a == b {
+            inside[a] += w;
+        }
+        degree[a] += w;
+        degree[b] += w;
+        m += w;
+    }
+    if m == 0.0 {
+        return 0.0;
+    }
+    inside
+        .iter()
+        .zip(&degree)
+        .map(|(l, d)| l / m - resolution * (d / (2.0 * m)).powi(2))
+        .sum()
+}
+
+/// Girvan and Newman's divisive clustering
+/// (https://doi.org/10.1073/pnas.122653799): remove the edge with the
+/// highest betweenness, recompute, and repeat until no edges are left. Each
+/// time a component splits, the new components are recorded as a level of the
+/// hierarchy, scored by modularity on the original graph. Edges are unweighted
+/// and ties go to the edge with the lowest index.
+///
+/// Every removal recomputes all shortest paths, so this takes O(m² n) time
+/// and is only practical for small graphs.
+pub fn girvan_newman<N: Sync, E>(graph: &UnGraph<N, E>) -> GirvanNewman {
+    // Remember each edge's index in `graph` as we remove edges from the copy.
+    let mut remaining: UnGraph<(), EdgeIndex> = graph.map(|_, _| (), |e, _| e);
+    let partition = |g: &UnGraph<(), EdgeIndex>| {
+        let community = weak_components(g);
+        let modularity = modularity(graph, |_| 1.0, &community, 1.0);
+        Partition { community, modularity }
+    };
+
+    let mut levels = vec![partition(&remaining)];
+    let mut removed = Vec::with_capacity(graph.edge_count());
+    while remaining.edge_count() > 0 {
+        let betweenness = edge_betweenness(&remaining, |_| 1.0);
+        let top = remaining
+            .edge_indices()
+            .max_by(|&a, &b| {
+                let by_score = betweenness[a.index()].total_cmp(&betweenness[b.index()]);
+                by_score.then(remaining[b].cmp(&remaining[a]))
+            })
+            .unwrap();
+        removed.push(remaining.remove_edge(top).unwrap());
+        let next = partition(&remaining);
+        if next.len() > levels.last().unwrap().len() {
+            levels.push(next);
+        }
+    }
+
+    let best = (0..levels.len())
+        .rev()
+        .max_by(|&a, &b| levels[a].modularity.total_cmp(&levels[b].modularity))
+        .unwrap();
+    GirvanNewman { levels, removed, best }
+}
+
+/// Blondel et al.'s Louvain method (https://arxiv.org/abs/0803.0476) with
+/// modularity at the given `resolution`. Each pass moves single vertices to
+/// the neighboring community that raises modularity the most, visiting them in
+/// node index order, then merges every community into one vertex and repeats
+/// on the smaller graph until nothing moves. Edge weights must be positive.
+///
+/// Runs in roughly O(m) per pass, so unlike `girvan_newman` it copes with
+/// large graphs. The result is a local optimum that depends on the visiting
+/// order.
+pub fn louvain<N, E, F>(graph: &UnGraph<N, E>, edge_weight: F, resolution: f64) -> Partition
+where
+    F: Fn(EdgeReference<E>) -> f64,
+{
+    // Symmetric adjacency with self-loops stored once, so that merged
+    // communities can keep their inside weight as a loop.
+    let mut adjacency: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); graph.node_count()];
+    for edge in graph.edge_references() {
+        let (u, v, w) = (edge.source().index(), edge.target().index(), edge_weight(edge));
+        *adjacency[u].entry(v).or_insert(0.0) += w;
+        if u != v {
+            *adjacency[v].entry(u).or_insert(0.0) += w;
+        }
+    }
+    let mut membership: Vec<usize> = (0..graph.node_count()).collect();
+    loop {
+        let (community, moved) = local_moves(&adjacency, resolution);
+        if !moved {
+            break;
+        }
+        let community = renumber(&community);
+        membership.iter_mut().for_each(|c| *c = community[*c]);
+        adjacency = aggregate(&adjacency, &community);
+    }
+
+    let community = renumber(&membership);
+    let modularity = modularity(graph, edge_weight, &community, resolution);
+    Partition { community, modularity }
+}
+
+/// The first phase of Louvain: move vertices between communities until no
+/// single move improves modularity. Returns each vertex's community and
+/// whether anything moved.
+fn local_moves(adjacency: &[BTreeMap<usize, f64>], resolution: f64) -> (Vec<usize>, bool) {
+    let n = adjacency.len();
+    let degree: Vec<f64> = adjacency
+        .iter()
+        .enumerate()
+        .map(|(u, row)| row.iter().map(|(&v, w)| if u == v { 2.0 * w } else { *w }).sum())
+        .collect();
+    let two_m: f64 = degree.iter().sum();
+    let mut community: Vec<usize> = (0..n).collect();
+    let mut total = degree.clone();
+    let mut moved = false;
+    if two_m == 0.0 {
+        return (community, moved);
+    }
+    loop {
+        let mut improved = false;
+        for u in 0..n {
+            let current = community[u];
+            total[current] -= degree[u];
+            // Weight from u into each neighboring community.
+            let mut links: BTreeMap<usize, f64> = BTreeMap::from([(current, 0.0)]);
+            for (&v, &w) in &adjacency[u] {
+                if v != u {
+                    *links.entry(community[v]).or_insert(0.0) += w;
+                }
+            }
+            // Gain of inserting u into c, up to a constant factor.
+            let gain = |c: usize, w: f64| w - resolution * total[c] * degree[u] / two_m;
+            let mut best = (current, gain(current, links[&current]));
+            for (&c, &w) in &links {
+                let g = gain(c, w);
+                if g > best.1 {
+                    best = (c, g);
+                }
+            }
+            total[best.0] += degree[u];
+            if best.0 != current {
+                community[u] = best.0;
+                improved = true;
+                moved = true;
+            }
+        }
+        if !improved {
+            return (community, moved);
+        }
+    }
+}
+
+/// The second phase of Louvain: one vertex per community, joined by the
+/// total weight between communities.
+fn aggregate(adjacency: &[BTreeMap<usize, f64>], community: &[usize]) -> Vec<BTreeMap<usize, f64>> {
+    let k = community.iter().max().map_or(0, |c| c + 1);
+    let mut merged: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); k];
+    for (u, row) in adjacency.iter().enumerate() {
+        for (&v, &w) in row.range(u..) {
+            let (a, b) = (community[u], community[v]);
+            *merged[a].entry(b).or_insert(0.0) += w;
+            if a != b {
+                *merged[b].entry(a).or_insert(0.0) += w;
+            }
+        }
+    }
+    merged
+}
+
+/// Number communities from zero in order of their first vertex.
+fn renumber(community: &[usize]) -> Vec<usize> {
+    let mut label = BTreeMap::new();
+    community
+        .iter()
+        .map(|&c| {
+            let next = label.len();
+            *label.entry(c).or_insert(next)
+        })
+        .collect()
+}
+
+/// The graph in DOT with every vertex filled in its community's color, from
+/// Graphviz's twelve-color `set312` scheme. Communities beyond the twelfth
+/// reuse the colors.
+pub fn dot<N: Display, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, community: &[usize]) -> String {
+    // Dot wants displayable edge weights even when it doesn't print them.
+    let graph = graph.map(|_, n| n, |_, _| "");
+    let edge = |_, _| String::new();
+    let node = |_, (v, _): (NodeIndex, _)| {
+        format!("style=filled colorscheme=set312 fillcolor={}", community[v.index()] % 12 + 1)
+    };
+    let dot = Dot::with_attr_getters(&graph, &[Config::EdgeNoLabel], &edge, &node);
+    format!("{dot}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::friends;
+
+    fn edge(graph: &UnGraph<&str, ()>, betweenness: &[f64], u: &str, v: &str) -> f64 {
+        let find = |name: &str| graph.node_indices().find(|&x| graph[x] == name).unwrap();
+        betweenness[graph.find_edge(find(u), find(v)).unwrap().index()]
+    }
+
+    #[test]
+    fn networkx_edge_betweenness() {
+        // nx.edge_betweenness_centrality(G, normalized=False)
+        let graph = friends();
+        let betweenness = edge_betweenness(&graph, |_| 1.0);
+        let expected = [
+            ("Adin", "Phil", 11.0), ("Adin", "Ivan", 32.0 / 3.0), ("Kim", "Adin", 59.0 / 6.0),
+            ("Kim", "Felix", 26.0 / 3.0), ("Frank", "Kim", 89.0 / 12.0), ("David", "Adin", 73.0 / 12.0),
+            ("Kim", "Jose", 6.0), ("Ivan", "Jean", 4.0), ("David", "Sven", 3.5),
+        ];
+        for (u, v, x) in expected {
+            let actual = edge(&graph, &betweenness, u, v);
+            assert!((actual - x).abs() < 1e-9, "{u}-{v}: {actual} != {x}");
+        }
+        // Every pair's path adds its length to the total.
+        let total: f64 = betweenness.iter().sum();
+        let pairs = crate::distance::Distances::new(&graph, |_| 1.0).average_path_length() * 66.0;
+        assert!((total - pairs).abs() < 1e-9);
+    }
+
+    #[test]
+    fn networkx_girvan_newman() {
+        // max(girvan_newman(G), key=lambda c: modularity(G, c))
+        let graph = friends();
+        let result = girvan_newman(&graph);
+        assert_eq!(result.removed.len(), graph.edge_count());
+        assert_eq!(result.levels.len(), graph.node_count());
+        assert!(result.levels.iter().enumerate().all(|(i, p)| p.len() == i + 1));
+
+        let best = &result.levels[result.best];
+        assert!((best.modularity - 0.1315789474).abs() < 1e-9);
+        let scores = best.scores(&graph);
+        let group = |names: &[&str]| names.iter().map(|n| *scores.get_by_weight(n).unwrap()).collect::<Vec<_>>();
+        let ivan = group(&["Ivan", "Jean", "Felix"]);
+        assert!(ivan.iter().all(|&c| c == ivan[0]));
+        let rest = group(&["Frank", "Kim", "David", "Adin", "Maria", "Jose", "Jasmine", "Sven"]);
+        assert!(rest.iter().all(|&c| c == rest[0]));
+        let phil = group(&["Phil"]);
+        assert!(phil[0] != ivan[0] && phil[0] != rest[0] && ivan[0] != rest[0]);
+    }
+
+    #[test]
+    fn modularity_of_two_triangles() {
+        // Two triangles joined by one edge: 2 * (3/7 - (7/14)²).
+        let mut graph: UnGraph<&str, ()> = Graph::new_undirected();
+        let v: Vec<_> = (0..6).map(|_| graph.add_node("")).collect();
+        graph.extend_with_edges([
+            (v[0], v[1]), (v[1], v[2]), (v[2], v[0]),
+            (v[3], v[4]), (v[4], v[5]), (v[5], v[3]),
+            (v[2], v[3]),
+        ]);
+        let split = [0, 0, 0, 1, 1, 1];
+        assert!((modularity(&graph, |_| 1.0, &split, 1.0) - 5.0 / 14.0).abs() < 1e-12);
+        assert_eq!(modularity(&graph, |_| 1.0, &[0; 6], 1.0), 0.0);
+        let result = girvan_newman(&graph);
+        assert_eq!(result.levels[result.best].community, split);
+        assert_eq!(result.removed[0], graph.find_edge(v[2], v[3]).unwrap());
+    }
+
+    #[test]
+    fn louvain_friends() {
+        // networkx's louvain_communities(G) finds 0.2091 or 0.2216 depending
+        // on the seed, both well above the best Girvan–Newman split.
+        let graph = friends();
+        let partition = louvain(&graph, |_| 1.0, 1.0);
+        assert!(partition.modularity > 0.2, "{}", partition.modularity);
+        assert_eq!(partition.modularity, modularity(&graph, |_| 1.0, &partition.community, 1.0));
+        let girvan_newman = girvan_newman(&graph);
+        assert!(partition.modularity > girvan_newman.levels[girvan_newman.best].modularity);
+
+        // A low resolution puts everyone together: 1 - 0.5 * 1².
+        let partition = louvain(&graph, |_| 1.0, 0.5);
+        assert_eq!(partition.community, [0; 12]);
+        assert!((partition.modularity - 0.5).abs() < 1e-12);
+        // A high one breaks the graph up further.
+        assert!(louvain(&graph, |_| 1.0, 2.0).len() > louvain(&graph, |_| 1.0, 1.0).len());
+    }
+
+    #[test]
+    fn louvain_weights() {
+        // Two triangles joined by one edge. Unweighted, the triangles are the
+        // communities. When the bridge outweighs everything else, its ends
+        // belong together.
+        let mut graph: UnGraph<&str, f64> = Graph::new_undirected();
+        let v: Vec<_> = (0..6).map(|_| graph.add_node("")).collect();
+        graph.extend_with_edges([
+            (v[0], v[1], 1.0), (v[1], v[2], 1.0), (v[2], v[0], 1.0),
+            (v[3], v[4], 1.0), (v[4], v[5], 1.0), (v[5], v[3], 1.0),
+            (v[2], v[3], 20.0),
+        ]);
+        let partition = louvain(&graph, |_| 1.0, 1.0);
+        assert_eq!(partition.community, [0, 0, 0, 1, 1, 1]);
+        assert!((partition.modularity - 5.0 / 14.0).abs() < 1e-12);
+        let partition = louvain(&graph, |e| *e.weight(), 1.0);
+        assert_eq!(partition.community[2], partition.community[3]);
+        assert!(partition.modularity > modularity(&graph, |e| *e.weight(), &[0, 0, 0, 1, 1, 1], 1.0));
+    }
+
+    #[test]
+    fn dot_colors() {
+        let graph = friends();
+        let partition = louvain(&graph, |_| 1.0, 1.0);
+        let dot = dot(&graph, &partition.community);
+        assert!(dot.starts_with("graph {"));
+        assert_eq!(dot.matches("fillcolor=").count(), graph.node_count());
+        assert_eq!(dot.matches(" -- ").count(), graph.edge_count());
+        assert!(dot.contains("label = \"Kim\""));
+    }
+}
@@ -0,0 +1,318 @@
+//! Connected components, strongly connected components, articulation points,
+//! bridges and the condensation of a directed graph.
+//!
+//! Components are numbered from zero, indexed by `NodeIndex::index()`. Weak
+//! components are numbered in order of their first vertex and strong ones in
+//! topological order of the condensation, so an edge between two strong
+//! components always goes from the lower number to the higher.
+
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{DiGraph, EdgeIndex, Graph, NodeIndex};
+use petgraph::unionfind::UnionFind;
+use petgraph::visit::EdgeRef;
+use petgraph::{Direction, EdgeType};
+
+/// Weak and strong components of a graph. For an undirected graph the two
+/// are the same.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Connectivity {
+    pub weak: Vec<usize>,
+    pub strong: Vec<usize>,
+}
+
+impl Connectivity {
+    pub fn of<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Self {
+        Connectivity { weak: weak_components(graph), strong: strong_components(graph) }
+    }
+
+    pub fn weak_count(&self) -> usize {
+        count(&self.weak)
+    }
+
+    pub fn strong_count(&self) -> usize {
+        count(&self.strong)
+    }
+
+    /// Every vertex can reach every other if edge directions are ignored.
+    pub fn is_connected(&self) -> bool {
+        self.weak_count() <= 1
+    }
+
+    /// Every vertex can reach every other following edge directions.
+    pub fn is_strongly_connected(&self) -> bool {
+        self.strong_count() <= 1
+    }
+
+    /// What becomes of `measure` on this graph, or `None` if every vertex can
+    /// reach every other. Measures that assume they can get a warning, and
+    /// ones that cope get a note saying how.
+    pub fn diagnose(&self, measure: Measure) -> Option<String> {
+        if self.is_strongly_connected() {
+            return None;
+        }
+        let (level, name, effect) = match measure {
+            Measure::Closeness => (
+                "warning",
+                "closeness",
+                "overrates vertices that reach few others; consider Wasserman-Faust or harmonic centrality",
+            ),
+            Measure::Eigenvector => (
+                "warning",
+                "eigenvector centrality",
+                "depends on the starting vector; consider Katz centrality or PageRank",
+            ),
+            Measure::Hits => ("warning", "HITS", "depends on the starting vector"),
+            Measure::Betweenness => ("note", "betweenness", "only counts paths within a component"),
+            Measure::Harmonic => ("note", "harmonic centrality", "counts unreachable vertices as infinitely far away"),
+            Measure::Katz => ("note", "Katz centrality", "gives vertices nobody reaches just the constant term"),
+            Measure::PageRank => ("note", "PageRank", "reaches the other components by teleporting"),
+        };
+        Some(format!("{level}: {name} on a graph with {} strongly connected components {effect}", self.strong_count()))
+    }
+
+    /// Print `diagnose` to stderr, if it has anything to say.
+    pub fn warn(&self, measure: Measure) {
+        if let Some(message) = self.diagnose(measure) {
+            eprintln!("{message}");
+        }
+    }
+}
+
+/// Centrality measures whose meaning changes when some vertices can't reach
+/// others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Measure {
+    Betweenness,
+    Closeness,
+    Harmonic,
+    Eigenvector,
+    Katz,
+    Hits,
+    PageRank,
+}
+
+fn count(component: &[usize]) -> usize {
+    component.iter().max().map_or(0, |c| c + 1)
+}
+
+/// Components of the graph with edge directions ignored.
+pub fn weak_components<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<usize> {
+    let mut sets = UnionFind::new(graph.node_count());
+    for edge in graph.edge_references() {
+        sets.union(edge.source().index(), edge.target().index());
+    }
+    let mut label = vec![usize::MAX; graph.node_count()];
+    let mut next = 0;
+    (0..graph.node_count())
+        .map(|v| {
+            let root = sets.find(v);
+            if label[root] == usize::MAX {
+                label[root] = next;
+                next += 1;
+            }
+            label[root]
+        })
+        .collect()
+}
+
+/// Strongly connected components by Tarjan's algorithm, numbered in
+/// topological order.
+pub fn strong_components<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<usize> {
+    // tarjan_scc lists the components in reverse topological order.
+    let components = tarjan_scc(graph);
+    let k = components.len();
+    let mut label = vec![0; graph.node_count()];
+    for (i, component) in components.iter().enumerate() {
+        for v in component {
+            label[v.index()] = k - 1 - i;
+        }
+    }
+    label
+}
+
+/// One vertex per strong component, holding its members, with an edge
+/// wherever the original graph has one between components. Vertex i is
+/// component i from `strong_components`, so the vertex indices are already
+/// a topological order.
+pub fn condensation<N, E>(graph: &DiGraph<N, E>) -> DiGraph<Vec<NodeIndex>, ()> {
+    let strong = strong_components(graph);
+    let mut dag = DiGraph::with_capacity(count(&strong), 0);
+    for _ in 0..count(&strong) {
+        dag.add_node(Vec::new());
+    }
+    for v in graph.node_indices() {
+        dag[NodeIndex::new(strong[v.index()])].push(v);
+    }
+    for edge in graph.edge_references() {
+        let a = NodeIndex::new(strong[edge.source().index()]);
+        let b = NodeIndex::new(strong[edge.target().index()]);
+        if a != b && dag.find_edge(a, b).is_none() {
+            dag.add_edge(a, b, ());
+        }
+    }
+    dag
+}
+
+/// Cut vertices and bridges of the graph with edge directions ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cuts {
+    /// Vertices whose removal disconnects their component, in index order.
+    pub articulation_points: Vec<NodeIndex>,
+    /// Edges whose removal disconnects their component, in index order.
+    pub bridges: Vec<EdgeIndex>,
+}
+
+/// Articulation points and bridges from one depth-first search, using
+/// Hopcroft and Tarjan's low points. Parallel edges are never bridges.
+pub fn cuts<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Cuts {
+    let n = graph.node_count();
+    let mut discovered = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut is_cut = vec![false; n];
+    let mut bridges = Vec::new();
+    let mut time = 0;
+
+    for root in graph.node_indices() {
+        if discovered[root.index()] != usize::MAX {
+            continue;
+        }
+        discovered[root.index()] = time;
+        low[root.index()] = time;
+        time += 1;
+        let mut root_children = 0;
+        // (vertex, edge we arrived by, remaining incident edges)
+        let incident = |v: NodeIndex| {
+            graph
+                .edges_directed(v, Direction::Outgoing)
+                .chain(graph.edges_directed(v, Direction::Incoming).filter(|_| graph.is_directed()))
+                .map(move |e| (e.id(), if e.source() == v { e.target() } else { e.source() }))
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+        let mut stack = vec![(root, None, incident(root))];
+        while let Some((v, via, edges)) = stack.last_mut() {
+            let (v, via) = (*v, *via);
+            match edges.next() {
+                Some((e, _)) if Some(e) == via => (),
+                Some((_, w)) if discovered[w.index()] != usize::MAX => {
+                    low[v.index()] = low[v.index()].min(discovered[w.index()]);
+                }
+                Some((e, w)) => {
+                    discovered[w.index()] = time;
+                    low[w.index()] = time;
+                    time += 1;
+                    if v == root {
+                        root_children += 1;
+                    }
+                    stack.push((w, Some(e), incident(w)));
+                }
+                None => {
+                    stack.pop();
+                    if let Some(&(parent, _, _)) = stack.last() {
+                        low[parent.index()] = low[parent.index()].min(low[v.index()]);
+                        if low[v.index()] > discovered[parent.index()] {
+                            bridges.push(via.unwrap());
+                        }
+                        if parent != root && low[v.index()] >= discovered[parent.index()] {
+                            is_cut[parent.index()] = true;
+                        }
+                    }
+                }
+            }
+        }
+        is_cut[root.index()] = root_children > 1;
+    }
+
+    bridges.sort();
+    Cuts {
+        articulation_points: graph.node_indices().filter(|v| is_cut[v.index()]).collect(),
+        bridges,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::prelude::*;
+
+    fn from_dot<Ty: EdgeType>(source: &str) -> Graph<String, (), Ty> {
+        let dot = crate::dot::parse(source).unwrap();
+        let mut graph = Graph::default();
+        for node in &dot.nodes {
+            graph.add_node(node.name.clone());
+        }
+        graph.extend_with_edges(dot.edges.iter().map(|e| (e.tail as u32, e.head as u32)));
+        graph
+    }
+
+    fn names<Ty: EdgeType>(graph: &Graph<String, (), Ty>, nodes: &[NodeIndex]) -> Vec<String> {
+        let mut names: Vec<_> = nodes.iter().map(|&v| graph[v].clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn dag() {
+        let graph: DiGraph<String, ()> = from_dot(include_str!("../../dag.dot"));
+        let connectivity = Connectivity::of(&graph);
+        assert_eq!(connectivity.weak_count(), 2);
+        assert!(!connectivity.is_connected());
+        // A -> B -> C -> D is acyclic, but a -> b -> c -> a is a cycle.
+        assert_eq!(connectivity.strong_count(), 6);
+        assert_eq!(
+            connectivity.diagnose(Measure::Betweenness).unwrap(),
+            "note: betweenness on a graph with 6 strongly connected components only counts paths within a component"
+        );
+        assert!(connectivity.diagnose(Measure::Closeness).unwrap().starts_with("warning: closeness"));
+
+        let dag = condensation(&graph);
+        assert_eq!(dag.node_count(), 6);
+        let cycle = dag.node_indices().find(|&c| dag[c].len() == 3).unwrap();
+        assert_eq!(names(&graph, &dag[cycle]), ["a", "b", "c"]);
+        // Component numbers are a topological order.
+        assert!(dag.edge_references().all(|e| e.source() < e.target()));
+        assert_eq!(dag.edge_count(), 5);
+
+        // Ignoring directions, A-B-C-D-A is a cycle, and c is the only way
+        // to d.
+        let cuts = cuts(&graph);
+        assert_eq!(names(&graph, &cuts.articulation_points), ["c"]);
+        let bridges: Vec<_> = cuts.bridges.iter().map(|&e| graph.edge_endpoints(e).unwrap()).collect();
+        assert_eq!(bridges.len(), 1);
+        assert_eq!(names(&graph, &[bridges[0].0, bridges[0].1]), ["c", "d"]);
+    }
+
+    #[test]
+    fn kingdom() {
+        let graph = crate::examples::kingdom();
+        let connectivity = Connectivity::of(&graph);
+        assert!(connectivity.is_connected());
+        assert!(connectivity.is_strongly_connected());
+        assert_eq!(connectivity.weak, connectivity.strong);
+        assert_eq!(connectivity.diagnose(Measure::Closeness), None);
+
+        // The only cycles are start - forest - mountains and
+        // start - sea - beach - city, and every road runs both ways, so
+        // none is a bridge.
+        let cuts = cuts(&graph);
+        let names = |nodes: &[NodeIndex]| {
+            let mut names: Vec<_> = nodes.iter().map(|&v| graph[v]).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&cuts.articulation_points), ["castle", "cave", "city", "forest", "mountains", "start"]);
+        assert!(cuts.bridges.is_empty());
+    }
+
+    #[test]
+    fn parallel_edges_are_not_bridges() {
+        let mut graph: UnGraph<(), ()> = Graph::new_undirected();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+        graph.extend_with_edges([(a, b), (a, b), (b, c)]);
+        let cuts = cuts(&graph);
+        assert_eq!(cuts.articulation_points, [b]);
+        assert_eq!(cuts.bridges, [EdgeIndex::new(2)]);
+    }
+}
@@ -0,0 +1,279 @@
+//! Directed acyclic graphs: cycle detection, topological orders by Kahn's
+//! algorithm and critical paths for scheduling.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::error::Error;
+use std::fmt;
+
+use petgraph::graph::{DiGraph, EdgeReference, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+
+/// The graph isn't a DAG. Holds one cycle, each vertex followed by its
+/// successor and the last one leading back to the first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle(pub Vec<NodeIndex>);
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nodes: Vec<_> = self.0.iter().chain(self.0.first()).map(|v| v.index().to_string()).collect();
+        write!(f, "the graph has a cycle: {}", nodes.join(" -> "))
+    }
+}
+
+impl Error for Cycle {}
+
+/// A cycle, if there is one, found by depth-first search from each vertex
+/// in index order. Self-loops count.
+pub fn find_cycle<N, E>(graph: &DiGraph<N, E>) -> Option<Cycle> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        OnPath,
+        Done,
+    }
+    let mut state = vec![State::New; graph.node_count()];
+    for root in graph.node_indices() {
+        if state[root.index()] != State::New {
+            continue;
+        }
+        state[root.index()] = State::OnPath;
+        let mut path = vec![(root, graph.neighbors(root).detach())];
+        while let Some((v, successors)) = path.last_mut() {
+            let v = *v;
+            match successors.next_node(graph) {
+                Some(w) if state[w.index()] == State::OnPath => {
+                    let start = path.iter().position(|&(u, _)| u == w).unwrap();
+                    return Some(Cycle(path[start..].iter().map(|&(u, _)| u).collect()));
+                }
+                Some(w) if state[w.index()] == State::New => {
+                    state[w.index()] = State::OnPath;
+                    path.push((w, graph.neighbors(w).detach()));
+                }
+                Some(_) => (),
+                None => {
+                    state[v.index()] = State::Done;
+                    path.pop();
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Kahn's algorithm. Whenever several vertices are ready, the one with the
+/// lowest index goes first, so the order is deterministic.
+pub fn topological_order<N, E>(graph: &DiGraph<N, E>) -> Result<Vec<NodeIndex>, Cycle> {
+    let mut in_degree = in_degrees(graph);
+    let mut ready: BinaryHeap<Reverse<NodeIndex>> = graph
+        .node_indices()
+        .filter(|v| in_degree[v.index()] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::with_capacity(graph.node_count());
+    while let Some(Reverse(v)) = ready.pop() {
+        order.push(v);
+        for w in graph.neighbors(v) {
+            in_degree[w.index()] -= 1;
+            if in_degree[w.index()] == 0 {
+                ready.push(Reverse(w));
+            }
+        }
+    }
+    if order.len() < graph.node_count() {
+        return Err(find_cycle(graph).expect("Kahn's algorithm stalls only on a cycle"));
+    }
+    Ok(order)
+}
+
+/// Every topological order, in lexicographic order of node indices, by
+/// running Kahn's algorithm with backtracking over each choice of ready
+/// vertex. There can be up to n! of them, so stop after `limit`.
+pub fn all_topological_orders<N, E>(graph: &DiGraph<N, E>, limit: usize) -> Result<Vec<Vec<NodeIndex>>, Cycle> {
+    if let Some(cycle) = find_cycle(graph) {
+        return Err(cycle);
+    }
+    let mut search = Search {
+        in_degree: in_degrees(graph),
+        used: vec![false; graph.node_count()],
+        order: Vec::with_capacity(graph.node_count()),
+        orders: Vec::new(),
+        limit,
+    };
+    search.extend(graph);
+    Ok(search.orders)
+}
+
+struct Search {
+    in_degree: Vec<usize>,
+    used: Vec<bool>,
+    order: Vec<NodeIndex>,
+    orders: Vec<Vec<NodeIndex>>,
+    limit: usize,
+}
+
+impl Search {
+    fn extend<N, E>(&mut self, graph: &DiGraph<N, E>) {
+        if self.order.len() == graph.node_count() {
+            self.orders.push(self.order.clone());
+            return;
+        }
+        for v in graph.node_indices() {
+            if self.orders.len() >= self.limit {
+                return;
+            }
+            if self.used[v.index()] || self.in_degree[v.index()] > 0 {
+                continue;
+            }
+            self.used[v.index()] = true;
+            self.order.push(v);
+            graph.neighbors(v).for_each(|w| self.in_degree[w.index()] -= 1);
+            self.extend(graph);
+            graph.neighbors(v).for_each(|w| self.in_degree[w.index()] += 1);
+            self.order.pop();
+            self.used[v.index()] = false;
+        }
+    }
+}
+
+fn in_degrees<N, E>(graph: &DiGraph<N, E>) -> Vec<usize> {
+    graph
+        .node_indices()
+        .map(|v| graph.neighbors_directed(v, Direction::Incoming).count())
+        .collect()
+}
+
+/// The longest chain of dependent tasks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CriticalPath {
+    /// Time to finish everything when unrelated tasks run in parallel.
+    pub length: f64,
+    /// The tasks on the critical path, first to last.
+    pub path: Vec<NodeIndex>,
+    /// Earliest time each task can start, indexed by `NodeIndex::index()`.
+    pub earliest_start: Vec<f64>,
+    /// How long each task can slip without delaying the whole project. Zero
+    /// on the critical path.
+    pub slack: Vec<f64>,
+}
+
+/// The critical path method. Each vertex is a task taking `task_duration`,
+/// and an edge u -> v means v can't start until `lag` after u finishes. Use
+/// `|_| 0.0` for either when only the other carries durations.
+pub fn critical_path<N, E, D, L>(graph: &DiGraph<N, E>, task_duration: D, lag: L) -> Result<CriticalPath, Cycle>
+where
+    D: Fn(NodeIndex) -> f64,
+    L: Fn(EdgeReference<E>) -> f64,
+{
+    let order = topological_order(graph)?;
+    let n = graph.node_count();
+    let duration: Vec<f64> = graph.node_indices().map(&task_duration).collect();
+
+    // Forward pass: earliest start, remembering which predecessor set it.
+    let mut start = vec![0.0; n];
+    let mut before: Vec<Option<NodeIndex>> = vec![None; n];
+    for &v in &order {
+        for edge in graph.edges(v) {
+            let w = edge.target().index();
+            let ready = start[v.index()] + duration[v.index()] + lag(edge);
+            if ready > start[w] || before[w].is_none() && ready == start[w] {
+                start[w] = ready;
+                before[w] = Some(v);
+            }
+        }
+    }
+    let finish = |v: usize| start[v] + duration[v];
+    let last = (0..n).max_by(|&a, &b| finish(a).total_cmp(&finish(b)).then(b.cmp(&a)));
+    let length = last.map_or(0.0, finish);
+
+    // Backward pass: latest start that still finishes on time.
+    let mut latest = vec![length; n];
+    for &v in order.iter().rev() {
+        let mut latest_finish = length;
+        for edge in graph.edges(v) {
+            latest_finish = f64::min(latest_finish, latest[edge.target().index()] - lag(edge));
+        }
+        latest[v.index()] = latest_finish - duration[v.index()];
+    }
+
+    let mut path: Vec<NodeIndex> = std::iter::successors(last.map(NodeIndex::new), |v| before[v.index()]).collect();
+    path.reverse();
+    let slack = latest.iter().zip(&start).map(|(l, s)| l - s).collect();
+    Ok(CriticalPath { length, path, earliest_start: start, slack })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dot::read_digraph;
+
+    fn names(graph: &DiGraph<crate::dot::Node, crate::dot::Attributes>, nodes: &[NodeIndex]) -> Vec<String> {
+        nodes.iter().map(|&v| graph[v].name.clone()).collect()
+    }
+
+    #[test]
+    fn dag_dot_has_a_cycle() {
+        let graph = read_digraph(include_str!("../../dag.dot")).unwrap();
+        let cycle = topological_order(&graph).unwrap_err();
+        assert_eq!(names(&graph, &cycle.0), ["a", "b", "c"]);
+        assert_eq!(cycle.to_string(), "the graph has a cycle: 4 -> 5 -> 6 -> 4");
+        assert!(all_topological_orders(&graph, 10).is_err());
+
+        // The left-hand graph on its own is fine.
+        let mut left = graph.clone();
+        left.retain_nodes(|g, v| g[v].name.chars().all(|c| c.is_uppercase()));
+        assert_eq!(find_cycle(&left), None);
+        let order = topological_order(&left).unwrap();
+        assert_eq!(names(&left, &order), ["A", "B", "C", "D"]);
+        assert_eq!(all_topological_orders(&left, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn get_dressed() {
+        let graph = read_digraph(include_str!("../../get-dressed.dot")).unwrap();
+        let order = topological_order(&graph).unwrap();
+        assert_eq!(names(&graph, &order), ["hat", "socks", "shoes", "pants", "shirt", "coat"]);
+
+        // 6! orders, halved once for socks before shoes and once for shirt
+        // before coat.
+        let orders = all_topological_orders(&graph, usize::MAX).unwrap();
+        assert_eq!(orders.len(), 180);
+        assert_eq!(orders[0], order);
+        for order in &orders {
+            let position = |name: &str| order.iter().position(|&v| graph[v].name == name).unwrap();
+            assert!(position("socks") < position("shoes"));
+            assert!(position("shirt") < position("coat"));
+        }
+        assert_eq!(all_topological_orders(&graph, 7).unwrap().len(), 7);
+
+        // One minute per garment, worn by two people at once.
+        let plan = critical_path(&graph, |_| 1.0, |_| 0.0).unwrap();
+        assert_eq!(plan.length, 2.0);
+        assert_eq!(names(&graph, &plan.path), ["socks", "shoes"]);
+        assert_eq!(plan.slack, [1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn durations_from_attributes() {
+        let source = r#"digraph {
+            design [duration=3]; build [duration=5]; docs [duration=2]; ship [duration=1];
+            design -> build -> ship;
+            design -> docs;
+            docs -> ship [lag=4];
+        }"#;
+        let graph = read_digraph(source).unwrap();
+        let attribute = |attributes: &crate::dot::Attributes, key| {
+            attributes.get(key).map_or(0.0, |x: &String| x.parse().unwrap())
+        };
+        let plan = critical_path(&graph, |v| attribute(&graph[v].attributes, "duration"), |e| {
+            attribute(e.weight(), "lag")
+        })
+        .unwrap();
+        // design 0-3, docs 3-5, four days' lag, ship 9-10.
+        assert_eq!(plan.length, 10.0);
+        assert_eq!(names(&graph, &plan.path), ["design", "docs", "ship"]);
+        assert_eq!(plan.earliest_start, [0.0, 3.0, 3.0, 9.0]);
+        assert_eq!(plan.slack, [0.0, 1.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,525 @@
+//! Degree distributions and discrete power-law fits.
+//!
+//! The fit follows Clauset, Shalizi and Newman, "Power-law distributions in
+//! empirical data" (2009). For each candidate x_min, alpha is the maximum
+//! likelihood estimate for the discrete power law
+//! p(x) = x^-alpha / zeta(alpha, x_min) on x >= x_min, and the x_min chosen
+//! is the one whose fit is closest to the data by the Kolmogorov–Smirnov
+//! distance. A bootstrap gives a p-value for the fit, and likelihood ratio
+//! tests compare it with exponential and log-normal tails.
+
+use std::f64::consts::SQRT_2;
+use std::fmt;
+
+use petgraph::graph::Graph;
+use petgraph::{Direction, EdgeType};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::scores::{render_rows, Column, Format};
+
+/// Which edges to count. All three agree on an undirected graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Degree {
+    In,
+    Out,
+    Total,
+}
+
+/// The degree of every vertex in node index order.
+pub fn degrees<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, degree: Degree) -> Vec<usize> {
+    graph
+        .node_indices()
+        .map(|v| {
+            let count = |d| graph.edges_directed(v, d).count();
+            match degree {
+                _ if !graph.is_directed() => graph.edges(v).count(),
+                Degree::In => count(Direction::Incoming),
+                Degree::Out => count(Direction::Outgoing),
+                Degree::Total => count(Direction::Incoming) + count(Direction::Outgoing),
+            }
+        })
+        .collect()
+}
+
+/// How many times each value occurs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Distribution {
+    /// `counts[k]` is the number of vertices with degree k.
+    pub counts: Vec<usize>,
+}
+
+impl Distribution {
+    pub fn new(values: &[usize]) -> Self {
+        let mut counts = vec![0; values.iter().max().map_or(0, |&m| m + 1)];
+        for &x in values {
+            counts[x] += 1;
+        }
+        Distribution { counts }
+    }
+
+    pub fn of<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, degree: Degree) -> Self {
+        Distribution::new(&degrees(graph, degree))
+    }
+
+    /// Number of values counted.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    pub fn mean(&self) -> f64 {
+        let sum: usize = self.counts.iter().enumerate().map(|(k, c)| k * c).sum();
+        sum as f64 / self.total() as f64
+    }
+
+    /// Fraction of values equal to k.
+    pub fn pmf(&self) -> Vec<f64> {
+        let n = self.total() as f64;
+        self.counts.iter().map(|&c| c as f64 / n).collect()
+    }
+
+    /// The complementary CDF: fraction of values at least k.
+    pub fn ccdf(&self) -> Vec<f64> {
+        let n = self.total() as f64;
+        let mut at_least = 0;
+        let mut ccdf: Vec<f64> = self
+            .counts
+            .iter()
+            .rev()
+            .map(|&c| {
+                at_least += c;
+                at_least as f64 / n
+            })
+            .collect();
+        ccdf.reverse();
+        ccdf
+    }
+
+    /// The CCDF on log-log axes, one row for each positive value that
+    /// occurs, with the base-10 logarithms alongside for plotting.
+    pub fn render_ccdf(&self, format: Format) -> String {
+        let ccdf = self.ccdf();
+        let rows: Vec<[String; 5]> = (1..self.counts.len())
+            .filter(|&k| self.counts[k] > 0)
+            .map(|k| {
+                let x = k as f64;
+                [
+                    k.to_string(),
+                    self.counts[k].to_string(),
+                    format!("{:.6}", ccdf[k]),
+                    format!("{:.6}", x.log10()),
+                    format!("{:.6}", ccdf[k].log10()),
+                ]
+            })
+            .collect();
+        let header = ["degree", "count", "ccdf", "log10_degree", "log10_ccdf"].map(|name| (name, Column::Number));
+        render_rows(format, header, &rows)
+    }
+}
+
+/// A discrete power law fitted to the values at least `x_min`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PowerLaw {
+    pub alpha: f64,
+    pub x_min: usize,
+    /// Number of values at least `x_min`.
+    pub tail: usize,
+    /// Kolmogorov–Smirnov distance between the tail and the fit.
+    pub ks: f64,
+    pub log_likelihood: f64,
+}
+
+/// Fit a power law, trying every distinct positive value but the largest
+/// as x_min. `None` if there are fewer than two distinct positive values.
+pub fn fit_power_law(values: &[usize]) -> Option<PowerLaw> {
+    let mut sorted: Vec<usize> = values.iter().copied().filter(|&x| x > 0).collect();
+    sorted.sort_unstable();
+    let mut candidates = sorted.clone();
+    candidates.dedup();
+    candidates.pop();
+    candidates
+        .into_iter()
+        .map(|x_min| {
+            let start = sorted.partition_point(|&x| x < x_min);
+            fit_tail(&sorted[start..], x_min)
+        })
+        .min_by(|a, b| a.ks.total_cmp(&b.ks))
+}
+
+/// The maximum likelihood power law for a sorted tail. The log-likelihood
+/// is concave in alpha, so a golden section search finds its maximum.
+fn fit_tail(tail: &[usize], x_min: usize) -> PowerLaw {
+    let n = tail.len() as f64;
+    let sum_log: f64 = tail.iter().map(|&x| (x as f64).ln()).sum();
+    let q = x_min as f64;
+    let log_likelihood = |alpha: f64| -n * hurwitz_zeta(alpha, q).ln() - alpha * sum_log;
+    let alpha = golden_section(|alpha| -log_likelihood(alpha), 1.0 + 1e-6, 20.0, 1e-8);
+    let ks = ks_distance(tail, |x| 1.0 - hurwitz_zeta(alpha, x as f64 + 1.0) / hurwitz_zeta(alpha, q));
+    PowerLaw { alpha, x_min, tail: tail.len(), ks, log_likelihood: log_likelihood(alpha) }
+}
+
+/// The largest gap between the empirical CDF of a sorted sample and `cdf`,
+/// both as step functions on the integers.
+fn ks_distance(sorted: &[usize], cdf: impl Fn(usize) -> f64) -> f64 {
+    let n = sorted.len() as f64;
+    let mut distance: f64 = 0.0;
+    let mut i = 0;
+    while i < sorted.len() {
+        let x = sorted[i];
+        let below = i as f64 / n;
+        while i < sorted.len() && sorted[i] == x {
+            i += 1;
+        }
+        // Just below x the empirical CDF hasn't stepped up yet.
+        if x > sorted[0] {
+            distance = distance.max((cdf(x - 1) - below).abs());
+        }
+        distance = distance.max((cdf(x) - i as f64 / n).abs());
+    }
+    distance
+}
+
+impl PowerLaw {
+    /// p(x) for x at least `x_min`.
+    pub fn pmf(&self, x: usize) -> f64 {
+        (x as f64).powf(-self.alpha) / hurwitz_zeta(self.alpha, self.x_min as f64)
+    }
+
+    /// Clauset, Shalizi and Newman's goodness-of-fit test: the fraction of
+    /// synthetic data sets, drawn from the fit above `x_min` and from the
+    /// data below it, whose own best fit is further from them than this one
+    /// is from the data. Values above 0.1 mean the power law is plausible.
+    /// Synthetic power-law values use their approximation
+    /// x = ⌊(x_min - 1/2)(1 - r)^(-1/(alpha - 1)) + 1/2⌋.
+    pub fn p_value(&self, values: &[usize], samples: usize, seed: u64) -> f64 {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let body: Vec<usize> = values.iter().copied().filter(|&x| x < self.x_min).collect();
+        let n = body.len() + self.tail;
+        let mut further = 0;
+        for _ in 0..samples {
+            let synthetic: Vec<usize> = (0..n)
+                .map(|_| {
+                    if rng.gen_range(0..n) < self.tail || body.is_empty() {
+                        let r: f64 = rng.gen();
+                        let x = (self.x_min as f64 - 0.5) * (1.0 - r).powf(-1.0 / (self.alpha - 1.0)) + 0.5;
+                        x.min(usize::MAX as f64 / 2.0) as usize
+                    } else {
+                        body[rng.gen_range(0..body.len())]
+                    }
+                })
+                .collect();
+            if fit_power_law(&synthetic).is_none_or(|fit| fit.ks >= self.ks) {
+                further += 1;
+            }
+        }
+        further as f64 / samples as f64
+    }
+
+    /// Vuong's likelihood ratio test against the best fitting `alternative`
+    /// on the same tail.
+    pub fn compare(&self, values: &[usize], alternative: Alternative) -> Comparison {
+        let tail: Vec<usize> = values.iter().copied().filter(|&x| x >= self.x_min).collect();
+        let fit = alternative.fit(&tail, self.x_min);
+        let ratios: Vec<f64> = tail.iter().map(|&x| self.pmf(x).ln() - fit.log_pmf(x, self.x_min)).collect();
+        let n = ratios.len() as f64;
+        let ratio: f64 = ratios.iter().sum();
+        let mean = ratio / n;
+        let sigma = (ratios.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n).sqrt();
+        let p_value = if sigma > 0.0 { erfc(ratio.abs() / (2.0 * n).sqrt() / sigma) } else { 1.0 };
+        Comparison { fit, log_likelihood_ratio: ratio, normalized_ratio: ratio / (n.sqrt() * sigma), p_value }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alternative {
+    Exponential,
+    LogNormal,
+}
+
+impl Alternative {
+    fn fit(self, tail: &[usize], x_min: usize) -> Fit {
+        match self {
+            Alternative::Exponential => {
+                // p(x) = (1 - e^-lambda) e^(-lambda (x - x_min)) is geometric,
+                // with mean x_min + 1 / (e^lambda - 1).
+                let mean = tail.iter().sum::<usize>() as f64 / tail.len() as f64;
+                Fit::Exponential { lambda: (1.0 + 1.0 / (mean - x_min as f64)).ln() }
+            }
+            Alternative::LogNormal => {
+                let logs: Vec<f64> = tail.iter().map(|&x| (x as f64).ln()).collect();
+                let n = logs.len() as f64;
+                let mu = logs.iter().sum::<f64>() / n;
+                let sigma = (logs.iter().map(|l| (l - mu).powi(2)).sum::<f64>() / n).sqrt().max(0.1);
+                let minus_log_likelihood = |[mu, log_sigma]: [f64; 2]| {
+                    let fit = Fit::LogNormal { mu, sigma: log_sigma.exp() };
+                    -tail.iter().map(|&x| fit.log_pmf(x, x_min)).sum::<f64>()
+                };
+                let [mu, log_sigma] = nelder_mead(minus_log_likelihood, [mu, sigma.ln()], 1e-10, 2000);
+                Fit::LogNormal { mu, sigma: log_sigma.exp() }
+            }
+        }
+    }
+}
+
+/// An alternative to the power law, fitted by maximum likelihood to the
+/// values at least x_min.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fit {
+    Exponential { lambda: f64 },
+    /// A log-normal binned onto the integers: p(x) is the probability of
+    /// [x, x + 1), scaled by the probability of [x_min, ∞).
+    LogNormal { mu: f64, sigma: f64 },
+}
+
+impl Fit {
+    pub fn log_pmf(&self, x: usize, x_min: usize) -> f64 {
+        match *self {
+            Fit::Exponential { lambda } => (-(-lambda).exp_m1()).ln() - lambda * (x - x_min) as f64,
+            Fit::LogNormal { mu, sigma } => {
+                let upper = |x: usize| erfc(((x as f64).ln() - mu) / (sigma * SQRT_2));
+                ((upper(x) - upper(x + 1)) / upper(x_min)).ln()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Fit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fit::Exponential { lambda } => write!(f, "exponential with lambda {lambda:.4}"),
+            Fit::LogNormal { mu, sigma } => write!(f, "log-normal with mu {mu:.4} and sigma {sigma:.4}"),
+        }
+    }
+}
+
+/// The outcome of a likelihood ratio test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Comparison {
+    pub fit: Fit,
+    /// Positive when the power law fits better, negative when the
+    /// alternative does.
+    pub log_likelihood_ratio: f64,
+    /// The ratio divided by its standard deviation.
+    pub normalized_ratio: f64,
+    /// Chance of a ratio this far from zero if both fit equally well. Only
+    /// below 0.1 or so does the sign of the ratio mean anything.
+    pub p_value: f64,
+}
+
+/// The Hurwitz zeta function, the sum of (k + q)^-s over k >= 0, for s > 1
+/// and q > 0, by Euler–Maclaurin summation as in Cephes.
+pub fn hurwitz_zeta(s: f64, q: f64) -> f64 {
+    // (2k)! / B_2k
+    const A: [f64; 12] = [
+        12.0, -720.0, 30240.0, -1209600.0, 47900160.0, -1.8924375803183792e9, 7.47242496e10,
+        -2.950130727918164e12, 1.1646782814350067e14, -4.597978722407473e15,
+        1.8152105401943546e17, -7.166165256175667e18,
+    ];
+    let mut sum = q.powf(-s);
+    let mut a = q;
+    let mut b = 0.0;
+    let mut i = 0;
+    while i < 9 || a <= 9.0 {
+        i += 1;
+        a += 1.0;
+        b = a.powf(-s);
+        sum += b;
+        if (b / sum).abs() < f64::EPSILON {
+            return sum;
+        }
+    }
+    let w = a;
+    sum += b * w / (s - 1.0);
+    sum -= 0.5 * b;
+    let mut a = 1.0;
+    let mut k = 0.0;
+    for coefficient in A {
+        a *= s + k;
+        b /= w;
+        let t = a * b / coefficient;
+        sum += t;
+        if (t / sum).abs() < f64::EPSILON {
+            break;
+        }
+        k += 1.0;
+        a *= s + k;
+        b /= w;
+        k += 1.0;
+    }
+    sum
+}
+
+/// The complementary error function, with a relative error below 1.2e-7
+/// everywhere (Numerical Recipes' Chebyshev fit).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynomial = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let r = t * polynomial.exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// The minimum of a unimodal function on [a, b].
+fn golden_section(f: impl Fn(f64) -> f64, mut a: f64, mut b: f64, tolerance: f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut c = b - ratio * (b - a);
+    let mut d = a + ratio * (b - a);
+    let (mut fc, mut fd) = (f(c), f(d));
+    while b - a > tolerance {
+        if fc < fd {
+            b = d;
+            (d, fd) = (c, fc);
+            c = b - ratio * (b - a);
+            fc = f(c);
+        } else {
+            a = c;
+            (c, fc) = (d, fd);
+            d = a + ratio * (b - a);
+            fd = f(d);
+        }
+    }
+    (a + b) / 2.0
+}
+
+/// Nelder and Mead's simplex search for a minimum of `f` in two dimensions.
+/// Infinite or NaN values count as worse than anything finite.
+fn nelder_mead(f: impl Fn([f64; 2]) -> f64, start: [f64; 2], tolerance: f64, iterations: usize) -> [f64; 2] {
+    let g = |x: [f64; 2]| {
+        let y = f(x);
+        if y.is_nan() { f64::INFINITY } else { y }
+    };
+    let mut simplex = [start, [start[0] + 0.5, start[1]], [start[0], start[1] + 0.5]].map(|x| (g(x), x));
+    let along = |a: [f64; 2], b: [f64; 2], t: f64| [a[0] + t * (b[0] - a[0]), a[1] + t * (b[1] - a[1])];
+    for _ in 0..iterations {
+        simplex.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let [(best, _), _, (worst, worst_x)] = simplex;
+        if (worst - best).abs() <= tolerance * (1.0 + best.abs()) {
+            break;
+        }
+        let centroid = along(simplex[0].1, simplex[1].1, 0.5);
+        let reflected = along(centroid, worst_x, -1.0);
+        let fr = g(reflected);
+        if fr < best {
+            let expanded = along(centroid, worst_x, -2.0);
+            let fe = g(expanded);
+            simplex[2] = if fe < fr { (fe, expanded) } else { (fr, reflected) };
+        } else if fr < simplex[1].0 {
+            simplex[2] = (fr, reflected);
+        } else {
+            let contracted = along(centroid, worst_x, 0.5);
+            let fc = g(contracted);
+            if fc < worst {
+                simplex[2] = (fc, contracted);
+            } else {
+                let best_x = simplex[0].1;
+                for vertex in &mut simplex[1..] {
+                    let x = along(best_x, vertex.1, 0.5);
+                    *vertex = (g(x), x);
+                }
+            }
+        }
+    }
+    simplex.sort_by(|a, b| a.0.total_cmp(&b.0));
+    simplex[0].1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::barabasi_albert;
+    use petgraph::graph::DiGraph;
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() <= tolerance
+    }
+
+    // 50 ones, 20 twos and a tail out to 35.
+    fn sample() -> Vec<usize> {
+        let counts = [(1, 50), (2, 20), (3, 10), (4, 6), (5, 4), (6, 3), (8, 2), (10, 1), (13, 1), (20, 1), (35, 1)];
+        counts.iter().flat_map(|&(x, c)| std::iter::repeat_n(x, c)).collect()
+    }
+
+    #[test]
+    fn distributions() {
+        let mut graph = DiGraph::<(), ()>::new();
+        let v: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        for (a, b) in [(0, 1), (0, 2), (0, 3), (1, 2), (3, 2)] {
+            graph.add_edge(v[a], v[b], ());
+        }
+        assert_eq!(degrees(&graph, Degree::In), [0, 1, 3, 1]);
+        assert_eq!(degrees(&graph, Degree::Out), [3, 1, 0, 1]);
+        assert_eq!(degrees(&graph, Degree::Total), [3, 2, 3, 2]);
+        let undirected = graph.clone().into_edge_type::<petgraph::Undirected>();
+        assert_eq!(degrees(&undirected, Degree::In), [3, 2, 3, 2]);
+
+        let distribution = Distribution::of(&graph, Degree::In);
+        assert_eq!(distribution.counts, [1, 2, 0, 1]);
+        assert_eq!(distribution.pmf(), [0.25, 0.5, 0.0, 0.25]);
+        assert_eq!(distribution.ccdf(), [1.0, 0.75, 0.25, 0.25]);
+        assert_eq!(distribution.mean(), 1.25);
+        let expected = "degree,count,ccdf,log10_degree,log10_ccdf\n\
+                        1,2,0.750000,0.000000,-0.124939\n\
+                        3,1,0.250000,0.477121,-0.602060\n";
+        assert_eq!(distribution.render_ccdf(Format::Csv), expected);
+    }
+
+    #[test]
+    fn zeta() {
+        // mpmath.zeta(s, q)
+        assert!(close(hurwitz_zeta(2.0, 1.0), std::f64::consts::PI.powi(2) / 6.0, 1e-15));
+        assert!(close(hurwitz_zeta(2.5, 3.0), 0.1647105619542803, 1e-15));
+        assert!(close(hurwitz_zeta(1.5, 10.0), 0.6486616319415704, 1e-14));
+    }
+
+    #[test]
+    fn fit() {
+        // The same scan over x_min with mpmath's zeta and findroot picks
+        // x_min = 3 with alpha = 2.494419 and D = 0.043536.
+        let fit = fit_power_law(&sample()).unwrap();
+        assert_eq!(fit.x_min, 3);
+        assert_eq!(fit.tail, 29);
+        assert!(close(fit.alpha, 2.4944191055244636, 1e-6));
+        assert!(close(fit.ks, 0.0435360071371542, 1e-6));
+        assert!(close(fit.log_likelihood, -64.43244602077061, 1e-6));
+        assert_eq!(fit_power_law(&[0, 3, 3]), None);
+    }
+
+    #[test]
+    fn alternatives() {
+        // The exponential has lambda = ln(1 + 1 / (mean - 3)), and the
+        // power law beats it, though not significantly with 29 values.
+        let values = sample();
+        let fit = fit_power_law(&values).unwrap();
+        let exponential = fit.compare(&values, Alternative::Exponential);
+        assert_eq!(exponential.fit, Fit::Exponential { lambda: (1.0 + 1.0 / (186.0 / 29.0 - 3.0f64)).ln() });
+        assert!(close(exponential.log_likelihood_ratio, 4.058983528008234, 1e-6));
+        assert!(close(exponential.normalized_ratio, 1.1929824836769285, 1e-6));
+        assert!(close(exponential.p_value, 0.23287623318872355, 1e-6));
+
+        // A log-normal with a very negative mu looks like a power law and
+        // does a little better, but there's nothing to choose between them.
+        let log_normal = fit.compare(&values, Alternative::LogNormal);
+        assert!(log_normal.log_likelihood_ratio < 0.0);
+        assert!(log_normal.log_likelihood_ratio > -1.0);
+        assert!(log_normal.p_value > 0.5);
+    }
+
+    #[test]
+    fn preferential_attachment() {
+        // The degrees of a Barabási–Albert graph follow a power law with
+        // alpha = 3.
+        let graph = barabasi_albert(2000, 3, 7);
+        let values = degrees(&graph, Degree::Total);
+        let fit = fit_power_law(&values).unwrap();
+        assert!(close(fit.alpha, 3.0, 0.3), "alpha = {}", fit.alpha);
+        assert!(fit.p_value(&values, 20, 1) > 0.1);
+        let exponential = fit.compare(&values, Alternative::Exponential);
+        assert!(exponential.log_likelihood_ratio > 0.0);
+        assert!(exponential.p_value < 0.01);
+    }
+}
@@ -0,0 +1,257 @@
+//! All-pairs distances and the measures built on them: eccentricity,
+//! diameter, radius, center, periphery and average shortest path length.
+//!
+//! Unreachable pairs are infinitely far apart, so on a graph that isn't
+//! strongly connected every eccentricity, the diameter and the average path
+//! length are infinite. That is the textbook definition; use `harmonic`
+//! centrality or the Wasserman–Faust correction when a finite answer is
+//! wanted.
+
+use petgraph::graph::{EdgeReference, Graph, NodeIndex};
+use petgraph::EdgeType;
+
+use crate::paths::{for_each_source, ShortestPaths};
+use crate::scores::Scores;
+
+/// Shortest distance between every ordered pair of vertices, indexed by
+/// `NodeIndex::index()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distances {
+    n: usize,
+    /// Row-major, `f64::INFINITY` where there is no path.
+    matrix: Vec<f64>,
+}
+
+impl Distances {
+    /// Dijkstra from every vertex, in parallel. `edge_cost` gives the length
+    /// of each edge; `|_| 1.0` counts hops.
+    pub fn new<N, E, Ty, F>(graph: &Graph<N, E, Ty>, edge_cost: F) -> Self
+    where
+        N: Sync,
+        E: Sync,
+        Ty: EdgeType + Sync,
+        F: Fn(EdgeReference<E>) -> f64 + Sync,
+    {
+        let n = graph.node_count();
+        let mut matrix = Vec::with_capacity(n * n);
+        for_each_source(
+            graph,
+            edge_cost,
+            |sp: ShortestPaths<f64>| sp.dist,
+            |row| matrix.extend(row.into_iter().map(|d| d.unwrap_or(f64::INFINITY))),
+        );
+        Distances { n, matrix }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn get(&self, u: NodeIndex, v: NodeIndex) -> f64 {
+        self.matrix[u.index() * self.n + v.index()]
+    }
+
+    /// Distances from `u` to every vertex.
+    pub fn row(&self, u: NodeIndex) -> &[f64] {
+        &self.matrix[u.index() * self.n..(u.index() + 1) * self.n]
+    }
+
+    /// Distance from each vertex to the one farthest from it.
+    pub fn eccentricity(&self) -> Vec<f64> {
+        (0..self.n)
+            .map(|u| self.row(NodeIndex::new(u)).iter().copied().fold(0.0, f64::max))
+            .collect()
+    }
+
+    pub fn diameter(&self) -> f64 {
+        diameter(&self.eccentricity())
+    }
+
+    pub fn radius(&self) -> f64 {
+        radius(&self.eccentricity())
+    }
+
+    pub fn center(&self) -> Vec<NodeIndex> {
+        center(&self.eccentricity())
+    }
+
+    pub fn periphery(&self) -> Vec<NodeIndex> {
+        periphery(&self.eccentricity())
+    }
+
+    /// Mean distance over all ordered pairs of distinct vertices, infinite if
+    /// any pair is unreachable and NaN with fewer than two vertices.
+    pub fn average_path_length(&self) -> f64 {
+        let pairs = (self.n * self.n.saturating_sub(1)) as f64;
+        self.matrix.iter().sum::<f64>() / pairs
+    }
+}
+
+/// The largest eccentricity. Zero for an empty graph.
+pub fn diameter(eccentricity: &[f64]) -> f64 {
+    eccentricity.iter().copied().fold(0.0, f64::max)
+}
+
+/// The smallest eccentricity. Zero for an empty graph.
+pub fn radius(eccentricity: &[f64]) -> f64 {
+    if eccentricity.is_empty() {
+        return 0.0;
+    }
+    eccentricity.iter().copied().fold(f64::INFINITY, f64::min)
+}
+
+/// Vertices whose eccentricity is the radius.
+pub fn center(eccentricity: &[f64]) -> Vec<NodeIndex> {
+    with_eccentricity(eccentricity, radius(eccentricity))
+}
+
+/// Vertices whose eccentricity is the diameter.
+pub fn periphery(eccentricity: &[f64]) -> Vec<NodeIndex> {
+    with_eccentricity(eccentricity, diameter(eccentricity))
+}
+
+fn with_eccentricity(eccentricity: &[f64], e: f64) -> Vec<NodeIndex> {
+    eccentricity
+        .iter()
+        .enumerate()
+        .filter(|&(_, &x)| x == e)
+        .map(|(u, _)| NodeIndex::new(u))
+        .collect()
+}
+
+/// Harmonic centrality: the sum of 1 / d(u, v) over every vertex v that u
+/// can reach. Unreachable vertices are infinitely far away and add nothing,
+/// so unlike `closeness` this stays meaningful on disconnected graphs
+/// (Marchiori and Latora, https://arxiv.org/abs/cond-mat/0008357).
+///
+/// An isolated vertex, or one with no outgoing edges, scores zero. With
+/// `normalized` the sum is divided by n - 1, so 1 means u is adjacent to every
+/// other vertex (a graph with a single vertex scores zero). Edge costs must be
+/// positive.
+pub fn harmonic<N, E, Ty, F>(
+    graph: &Graph<N, E, Ty>,
+    edge_cost: F,
+    normalized: bool,
+) -> Scores<N>
+where
+    N: Clone + Sync,
+    E: Sync,
+    Ty: EdgeType + Sync,
+    F: Fn(EdgeReference<E>) -> f64 + Sync,
+{
+    let n = graph.node_count() as f64;
+    let mut result = Vec::with_capacity(graph.node_count());
+    for_each_source(
+        graph,
+        edge_cost,
+        |sp: ShortestPaths<f64>| {
+            // order[0] is the source itself, at distance zero.
+            sp.order[1..]
+                .iter()
+                .filter_map(|v| sp.dist[v.index()])
+                .fold(0.0, |sum, d| sum + 1.0 / d)
+        },
+        |mut harmonic| {
+            if normalized {
+                harmonic = if n > 1.0 { harmonic / (n - 1.0) } else { 0.0 };
+            }
+            result.push(harmonic);
+        },
+    );
+    Scores::new(graph, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::{kingdom, two_components};
+    use petgraph::prelude::*;
+
+    fn names(graph: &DiGraph<&'static str, f64>, nodes: Vec<NodeIndex>) -> Vec<&'static str> {
+        nodes.into_iter().map(|v| graph[v]).collect()
+    }
+
+    #[test]
+    fn kingdom_weighted() {
+        // nx.eccentricity(G, weight="weight") and friends.
+        let graph = kingdom();
+        let distances = Distances::new(&graph, |e| *e.weight());
+        let treasure = graph.node_indices().find(|&v| graph[v] == "treasure").unwrap();
+        let inferno = graph.node_indices().find(|&v| graph[v] == "inferno").unwrap();
+        assert_eq!(distances.get(treasure, inferno), 298.0);
+        let eccentricity: Vec<_> = graph.node_indices().zip(distances.eccentricity()).map(|(v, e)| (graph[v], e)).collect();
+        assert_eq!(eccentricity, [
+            ("start", 193.0), ("forest", 235.0), ("mountains", 264.0), ("sea", 229.0),
+            ("city", 183.0), ("desert", 328.0), ("cave", 254.0), ("glacier", 289.0),
+            ("inferno", 325.0), ("beach", 212.0), ("castle", 222.0), ("treasure", 298.0),
+        ]);
+        assert_eq!(distances.diameter(), 328.0);
+        assert_eq!(distances.radius(), 183.0);
+        assert_eq!(names(&graph, distances.center()), ["city"]);
+        assert_eq!(names(&graph, distances.periphery()), ["desert"]);
+        assert!((distances.average_path_length() - 141.96969696969697).abs() < 1e-9);
+    }
+
+    #[test]
+    fn kingdom_hops() {
+        let graph = kingdom();
+        let distances = Distances::new(&graph, |_| 1.0);
+        assert_eq!(distances.diameter(), 6.0);
+        assert_eq!(distances.radius(), 3.0);
+        assert_eq!(names(&graph, distances.center()), ["start"]);
+        assert_eq!(names(&graph, distances.periphery()), ["inferno", "treasure"]);
+        assert!((distances.average_path_length() - 30.0 / 11.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn unreachable_is_infinite() {
+        // Without the road back from the treasure, nobody else is reachable
+        // from it.
+        let mut graph = kingdom();
+        let back = graph.edge_indices().next_back().unwrap();
+        graph.remove_edge(back);
+        let distances = Distances::new(&graph, |e| *e.weight());
+        let treasure = graph.node_indices().find(|&v| graph[v] == "treasure").unwrap();
+        assert_eq!(distances.row(treasure).iter().filter(|d| d.is_infinite()).count(), 11);
+        assert_eq!(distances.eccentricity()[treasure.index()], f64::INFINITY);
+        assert_eq!(distances.diameter(), f64::INFINITY);
+        assert_eq!(names(&graph, distances.periphery()), ["treasure"]);
+        assert_eq!(distances.radius(), 183.0);
+        assert_eq!(distances.average_path_length(), f64::INFINITY);
+    }
+
+    fn assert_harmonic<N: Clone + Sync>(graph: &UnGraph<N, ()>, normalized: bool, expected: &[f64]) {
+        let scores = harmonic(graph, |_| 1.0, normalized);
+        for (v, x) in graph.node_indices().zip(expected) {
+            assert!((scores[v] - x).abs() < 1e-9, "{v:?}: {} != {x}", scores[v]);
+        }
+    }
+
+    #[test]
+    fn harmonic_two_components() {
+        let graph = two_components();
+        let expected = [1.5, 2.0, 1.5, 1.0, 1.0, 0.0];
+        assert_harmonic(&graph, false, &expected);
+        assert_harmonic(&graph, true, &expected.map(|x| x / 5.0));
+    }
+
+    #[test]
+    fn harmonic_connected() {
+        // On a path, harmonic and closeness agree on the ranking.
+        let mut graph: UnGraph<(), ()> = Graph::new_undirected();
+        let v: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        graph.extend_with_edges(v.windows(2).map(|w| (w[0], w[1])));
+        assert_harmonic(&graph, false, &[25.0 / 12.0, 17.0 / 6.0, 3.0, 17.0 / 6.0, 25.0 / 12.0]);
+    }
+
+    #[test]
+    fn harmonic_single_vertex() {
+        let mut graph: UnGraph<(), ()> = Graph::new_undirected();
+        graph.add_node(());
+        assert_harmonic(&graph, true, &[0.0]);
+    }
+}
@@ -0,0 +1,752 @@
+//! Read DOT files such as `friends.dot` or `dag.dot`.
+//!
+//! `parse` builds a `DotGraph` from the DOT language without HTML strings:
+//! `strict` graphs and digraphs, node, edge and attribute statements, `a = b`
+//! graph attributes, edge chains (`a -> b -> c`), subgraphs, including
+//! subgraphs as edge endpoints (`a -> {b c}`), ports, quoted IDs joined with
+//! `+`, and comments. `read_digraph` turns a digraph into a petgraph
+//! `DiGraph`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+
+use petgraph::graph::{DiGraph, NodeIndex, UnGraph};
+
+pub type Attributes = BTreeMap<String, String>;
+
+/// A vertex read from DOT.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Node {
+    pub name: String,
+    pub attributes: Attributes,
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// An edge between two vertices, by their position in `DotGraph::nodes`.
+/// A port on either end becomes a `tailport` or `headport` attribute.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Edge {
+    pub tail: usize,
+    pub head: usize,
+    pub attributes: Attributes,
+}
+
+/// A subgraph, named or not, with the vertices mentioned inside it or
+/// inside its own subgraphs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Subgraph {
+    pub name: Option<String>,
+    /// Graph attributes set inside the subgraph.
+    pub attributes: Attributes,
+    pub nodes: Vec<usize>,
+}
+
+/// Everything in a DOT file. Node and edge defaults (`node [shape=box]`)
+/// are applied to the vertices and edges that follow them in the same
+/// subgraph, so the attributes here are the ones in force.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DotGraph {
+    pub strict: bool,
+    pub directed: bool,
+    pub name: Option<String>,
+    /// Graph attributes set outside any subgraph.
+    pub attributes: Attributes,
+    /// Vertices in order of first mention.
+    pub nodes: Vec<Node>,
+    /// Edges in the order they appear. A strict graph merges repeated edges
+    /// into the first, and an undirected one doesn't care which way round.
+    pub edges: Vec<Edge>,
+    /// Subgraphs in order of first appearance.
+    pub subgraphs: Vec<Subgraph>,
+}
+
+impl DotGraph {
+    /// The position of the vertex called `name` in `nodes`.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.name == name)
+    }
+}
+
+/// What went wrong and where, counting lines and columns from one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DotError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for DotError {}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// An unquoted ID or keyword: a name or a number.
+    Id(String),
+    Quoted(String),
+    Arrow,
+    Line,
+    Punct(char),
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+    /// Nothing but whitespace so far on this line.
+    blank: bool,
+}
+
+impl Lexer<'_> {
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        self.blank = c == '\n' || self.blank && c.is_whitespace();
+        Some(c)
+    }
+
+    fn error(&self, message: impl Into<String>) -> DotError {
+        DotError { line: self.line, column: self.column, message: message.into() }
+    }
+
+    /// The next token and the position it starts at.
+    fn next_token(&mut self) -> Result<Option<(Token, usize, usize)>, DotError> {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                // C preprocessor output.
+                Some('#') if self.blank => self.skip_line(),
+                Some('/') => {
+                    self.bump();
+                    match self.bump() {
+                        Some('/') => self.skip_line(),
+                        Some('*') => self.skip_block()?,
+                        _ => return Err(self.error("expected // or /*")),
+                    }
+                }
+                _ => break,
+            }
+        }
+        let (line, column) = (self.line, self.column);
+        let error = |message: String| Err(DotError { line, column, message });
+        let token = match self.bump() {
+            None => return Ok(None),
+            Some('"') => {
+                let mut id = String::new();
+                loop {
+                    match self.bump() {
+                        None => return error("unterminated string".into()),
+                        Some('"') => break,
+                        // Only quotes are escaped, and a backslash before a
+                        // newline continues the line. Anything else, like
+                        // the \n in a label, is kept as it is. A doubled
+                        // backslash stays doubled, as in Graphviz, but can't
+                        // escape the quote after it.
+                        Some('\\') if self.chars.peek() == Some(&'"') => id.push(self.bump().unwrap()),
+                        Some('\\') if self.chars.peek() == Some(&'\\') => {
+                            id.push('\\');
+                            id.push(self.bump().unwrap());
+                        }
+                        Some('\\') if self.chars.peek() == Some(&'\n') => {
+                            self.bump();
+                        }
+                        Some(c) => id.push(c),
+                    }
+                }
+                Token::Quoted(id)
+            }
+            Some('-') if self.chars.peek() == Some(&'>') => {
+                self.bump();
+                Token::Arrow
+            }
+            Some('-') if self.chars.peek() == Some(&'-') => {
+                self.bump();
+                Token::Line
+            }
+            Some(c) if c == '-' || c == '.' || c.is_ascii_digit() => {
+                let mut number = c.to_string();
+                let mut point = c == '.';
+                while let Some(&c) = self.chars.peek() {
+                    if c == '.' && !point {
+                        point = true;
+                    } else if !c.is_ascii_digit() {
+                        break;
+                    }
+                    number.push(c);
+                    self.bump();
+                }
+                if !number.chars().any(|c| c.is_ascii_digit()) {
+                    return error(format!("expected a number, found {number:?}"));
+                }
+                if let Some(&c) = self.chars.peek().filter(|&&c| is_name_char(c) || c == '.') {
+                    return error(format!("unexpected {c:?} after the number {number}"));
+                }
+                Token::Id(number)
+            }
+            Some(c) if is_name_char(c) => {
+                let mut id = c.to_string();
+                while let Some(&c) = self.chars.peek().filter(|&&c| is_name_char(c) || c.is_ascii_digit()) {
+                    id.push(c);
+                    self.bump();
+                }
+                Token::Id(id)
+            }
+            Some('<') => return error("HTML strings aren't supported".into()),
+            Some(c) if "{}[];,=:+".contains(c) => Token::Punct(c),
+            Some(c) => return error(format!("unexpected {c:?}")),
+        };
+        Ok(Some((token, line, column)))
+    }
+
+    fn skip_line(&mut self) {
+        while self.chars.peek().is_some_and(|&c| c != '\n') {
+            self.bump();
+        }
+    }
+
+    fn skip_block(&mut self) -> Result<(), DotError> {
+        loop {
+            match self.bump() {
+                None => return Err(self.error("unterminated comment")),
+                Some('*') if self.chars.peek() == Some(&'/') => {
+                    self.bump();
+                    return Ok(());
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// A character that can start an unquoted name.
+fn is_name_char(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+/// Node and edge attributes for whatever comes next in a subgraph.
+#[derive(Clone, Debug, Default)]
+struct Defaults {
+    node: Attributes,
+    edge: Attributes,
+}
+
+/// One end of an edge.
+enum Operand {
+    Node { index: usize, port: Option<String> },
+    Subgraph(Vec<usize>),
+}
+
+impl Operand {
+    fn nodes(&self) -> &[usize] {
+        match self {
+            Operand::Node { index, .. } => std::slice::from_ref(index),
+            Operand::Subgraph(nodes) => nodes,
+        }
+    }
+
+    fn port(&self) -> Option<&String> {
+        match self {
+            Operand::Node { port, .. } => port.as_ref(),
+            Operand::Subgraph(_) => None,
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize, usize)>,
+    position: usize,
+    end: (usize, usize),
+    dot: DotGraph,
+    index: HashMap<String, usize>,
+    /// Edges of a strict graph by their ends.
+    edge_index: HashMap<(usize, usize), usize>,
+    /// The subgraphs being read, outermost first.
+    open: Vec<usize>,
+    /// Where every subgraph's defaults stood when it was last closed.
+    defaults: Vec<Defaults>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|t| &t.0)
+    }
+
+    fn peek_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn error(&self, message: impl Into<String>) -> DotError {
+        let (line, column) = self.tokens.get(self.position).map_or(self.end, |t| (t.1, t.2));
+        DotError { line, column, message: message.into() }
+    }
+
+    /// Skip over `c` if it's next.
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek_punct(c);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), DotError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{c}'")))
+        }
+    }
+
+    /// The keyword coming up, in lower case. Keywords aren't case sensitive.
+    fn keyword(&self) -> Option<&'static str> {
+        let Some(Token::Id(id)) = self.peek() else {
+            return None;
+        };
+        ["strict", "graph", "digraph", "node", "edge", "subgraph"].into_iter().find(|k| id.eq_ignore_ascii_case(k))
+    }
+
+    fn at_id(&self) -> bool {
+        matches!(self.peek(), Some(Token::Quoted(_))) || matches!(self.peek(), Some(Token::Id(_))) && self.keyword().is_none()
+    }
+
+    fn id(&mut self) -> Result<String, DotError> {
+        if !self.at_id() {
+            return Err(self.error("expected an ID"));
+        }
+        match self.tokens[self.position].0.clone() {
+            Token::Quoted(mut id) => {
+                self.position += 1;
+                while self.peek_punct('+') {
+                    self.position += 1;
+                    match self.peek() {
+                        Some(Token::Quoted(more)) => id.push_str(more),
+                        _ => return Err(self.error("expected a quoted string after '+'")),
+                    }
+                    self.position += 1;
+                }
+                Ok(id)
+            }
+            Token::Id(id) => {
+                self.position += 1;
+                Ok(id)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn node(&mut self, name: String, defaults: &Defaults) -> usize {
+        let nodes = &mut self.dot.nodes;
+        let index = *self.index.entry(name.clone()).or_insert_with(|| {
+            nodes.push(Node { name, attributes: defaults.node.clone() });
+            nodes.len() - 1
+        });
+        for &s in &self.open {
+            let members = &mut self.dot.subgraphs[s].nodes;
+            if !members.contains(&index) {
+                members.push(index);
+            }
+        }
+        index
+    }
+
+    fn add_edge(&mut self, tail: usize, head: usize, attributes: Attributes) {
+        if !self.dot.strict {
+            self.dot.edges.push(Edge { tail, head, attributes });
+            return;
+        }
+        let key = if self.dot.directed { (tail, head) } else { (tail.min(head), tail.max(head)) };
+        match self.edge_index.get(&key) {
+            Some(&e) => self.dot.edges[e].attributes.extend(attributes),
+            None => {
+                self.edge_index.insert(key, self.dot.edges.len());
+                self.dot.edges.push(Edge { tail, head, attributes });
+            }
+        }
+    }
+
+    /// The graph attributes of the innermost subgraph, or of the graph.
+    fn graph_attributes(&mut self) -> &mut Attributes {
+        match self.open.last() {
+            Some(&s) => &mut self.dot.subgraphs[s].attributes,
+            None => &mut self.dot.attributes,
+        }
+    }
+
+    /// Zero or more `[a=b, c=d]` lists.
+    fn attributes(&mut self) -> Result<Attributes, DotError> {
+        let mut attributes = Attributes::new();
+        while self.eat('[') {
+            while !self.peek_punct(']') {
+                let key = self.id()?;
+                self.expect('=')?;
+                let value = self.id()?;
+                attributes.insert(key, value);
+                if matches!(self.peek(), Some(Token::Punct(',' | ';'))) {
+                    self.position += 1;
+                }
+            }
+            self.expect(']')?;
+        }
+        Ok(attributes)
+    }
+
+    fn graph(&mut self, directed: Option<bool>) -> Result<(), DotError> {
+        if self.keyword() == Some("strict") {
+            self.position += 1;
+            self.dot.strict = true;
+        }
+        self.dot.directed = match self.keyword() {
+            Some("digraph") => true,
+            Some("graph") => false,
+            _ => return Err(self.error("expected 'graph' or 'digraph'")),
+        };
+        match directed {
+            Some(true) if !self.dot.directed => return Err(self.error("expected a digraph, not an undirected graph")),
+            Some(false) if self.dot.directed => return Err(self.error("expected an undirected graph, not a digraph")),
+            _ => self.position += 1,
+        }
+        if self.at_id() {
+            self.dot.name = Some(self.id()?);
+        }
+        self.expect('{')?;
+        self.statements(&mut Defaults::default())?;
+        if self.peek().is_some() {
+            return Err(self.error("unexpected text after the graph"));
+        }
+        Ok(())
+    }
+
+    /// Statements up to and including the closing brace.
+    fn statements(&mut self, defaults: &mut Defaults) -> Result<(), DotError> {
+        while !self.eat('}') {
+            if self.peek().is_none() {
+                return Err(self.error("expected '}'"));
+            }
+            self.statement(defaults)?;
+            self.eat(';');
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, defaults: &mut Defaults) -> Result<(), DotError> {
+        if let Some(kind @ ("graph" | "node" | "edge")) = self.keyword() {
+            self.position += 1;
+            if !self.peek_punct('[') {
+                return Err(self.error("expected '['"));
+            }
+            let attributes = self.attributes()?;
+            match kind {
+                "graph" => self.graph_attributes().extend(attributes),
+                "node" => defaults.node.extend(attributes),
+                _ => defaults.edge.extend(attributes),
+            }
+            return Ok(());
+        }
+        if self.at_id() && matches!(self.tokens.get(self.position + 1), Some((Token::Punct('='), ..))) {
+            let key = self.id()?;
+            self.position += 1;
+            let value = self.id()?;
+            self.graph_attributes().insert(key, value);
+            return Ok(());
+        }
+
+        let mut chain = vec![self.operand(defaults)?];
+        loop {
+            match (self.peek(), self.dot.directed) {
+                (Some(Token::Arrow), true) | (Some(Token::Line), false) => {
+                    self.position += 1;
+                    chain.push(self.operand(defaults)?);
+                }
+                (Some(Token::Line), true) => return Err(self.error("'--' in a digraph, expected '->'")),
+                (Some(Token::Arrow), false) => return Err(self.error("'->' in an undirected graph, expected '--'")),
+                _ => break,
+            }
+        }
+        if let [Operand::Subgraph(_)] = chain[..] {
+            return Ok(());
+        }
+        let attributes = self.attributes()?;
+        if let [Operand::Node { index, .. }] = chain[..] {
+            self.dot.nodes[index].attributes.extend(attributes);
+            return Ok(());
+        }
+        for pair in chain.windows(2) {
+            let mut edge = defaults.edge.clone();
+            if let Some(port) = pair[0].port() {
+                edge.insert("tailport".into(), port.clone());
+            }
+            if let Some(port) = pair[1].port() {
+                edge.insert("headport".into(), port.clone());
+            }
+            edge.extend(attributes.clone());
+            for &tail in pair[0].nodes() {
+                for &head in pair[1].nodes() {
+                    self.add_edge(tail, head, edge.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A vertex with an optional port, or a subgraph.
+    fn operand(&mut self, defaults: &Defaults) -> Result<Operand, DotError> {
+        if self.keyword() == Some("subgraph") || self.peek_punct('{') {
+            return self.subgraph(defaults.clone()).map(Operand::Subgraph);
+        }
+        let name = self.id()?;
+        let index = self.node(name, defaults);
+        let mut port = None;
+        if self.eat(':') {
+            let mut id = self.id()?;
+            if self.eat(':') {
+                id = format!("{id}:{}", self.id()?);
+            }
+            port = Some(id);
+        }
+        Ok(Operand::Node { index, port })
+    }
+
+    /// A subgraph, which starts with the defaults of the one around it, or
+    /// with its own if it's been seen before. Returns every vertex in it.
+    fn subgraph(&mut self, defaults: Defaults) -> Result<Vec<usize>, DotError> {
+        let mut name = None;
+        if self.keyword() == Some("subgraph") {
+            self.position += 1;
+            if self.at_id() {
+                name = Some(self.id()?);
+            }
+        }
+        let existing = name.as_ref().and_then(|name| self.dot.subgraphs.iter().position(|s| s.name.as_ref() == Some(name)));
+        let index = existing.unwrap_or_else(|| {
+            self.dot.subgraphs.push(Subgraph { name, ..Default::default() });
+            self.defaults.push(defaults);
+            self.dot.subgraphs.len() - 1
+        });
+        self.expect('{')?;
+        self.open.push(index);
+        let mut defaults = std::mem::take(&mut self.defaults[index]);
+        self.statements(&mut defaults)?;
+        self.defaults[index] = defaults;
+        self.open.pop();
+        Ok(self.dot.subgraphs[index].nodes.clone())
+    }
+}
+
+fn parse_expecting(source: &str, directed: Option<bool>) -> Result<DotGraph, DotError> {
+    let mut lexer = Lexer { chars: source.chars().peekable(), line: 1, column: 1, blank: true };
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token()? {
+        tokens.push(token);
+    }
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        end: (lexer.line, lexer.column),
+        dot: DotGraph::default(),
+        index: HashMap::new(),
+        edge_index: HashMap::new(),
+        open: Vec::new(),
+        defaults: Vec::new(),
+    };
+    parser.graph(directed)?;
+    Ok(parser.dot)
+}
+
+/// Parse a DOT graph or digraph.
+pub fn parse(source: &str) -> Result<DotGraph, DotError> {
+    parse_expecting(source, None)
+}
+
+/// Parse a DOT digraph. Vertices are added in order of first mention.
+pub fn read_digraph(source: &str) -> Result<DiGraph<Node, Attributes>, DotError> {
+    let dot = parse_expecting(source, Some(true))?;
+    let mut graph = DiGraph::with_capacity(dot.nodes.len(), dot.edges.len());
+    for node in dot.nodes {
+        graph.add_node(node);
+    }
+    for edge in dot.edges {
+        graph.add_edge(NodeIndex::new(edge.tail), NodeIndex::new(edge.head), edge.attributes);
+    }
+    Ok(graph)
+}
+
+/// Parse an undirected DOT graph. Vertices are added in order of first
+/// mention.
+pub fn read_graph(source: &str) -> Result<UnGraph<Node, Attributes>, DotError> {
+    let dot = parse_expecting(source, Some(false))?;
+    let mut graph = UnGraph::with_capacity(dot.nodes.len(), dot.edges.len());
+    for node in dot.nodes {
+        graph.add_node(node);
+    }
+    for edge in dot.edges {
+        graph.add_edge(NodeIndex::new(edge.tail), NodeIndex::new(edge.head), edge.attributes);
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repository_files() {
+        // (file, directed, vertices, edges)
+        let files = [
+            (include_str!("../../dag.dot"), true, 8, 8),
+            (include_str!("../../friends.dot"), false, 12, 19),
+            (include_str!("../../gale-shapley-ex2.dot"), true, 10, 9),
+            (include_str!("../../get-dressed.dot"), true, 6, 2),
+            (include_str!("../../kingdom.dot"), false, 12, 13),
+            (include_str!("../../paris-brussels-hague.dot"), false, 3, 2),
+        ];
+        for (source, directed, nodes, edges) in files {
+            let dot = parse(source).unwrap();
+            assert_eq!((dot.directed, dot.nodes.len(), dot.edges.len()), (directed, nodes, edges));
+            let counts = if directed {
+                read_digraph(source).map(|g| (g.node_count(), g.edge_count()))
+            } else {
+                read_graph(source).map(|g| (g.node_count(), g.edge_count()))
+            };
+            assert_eq!(counts, Ok((nodes, edges)));
+        }
+
+        let dressed = read_digraph(include_str!("../../get-dressed.dot")).unwrap();
+        let names: Vec<_> = dressed.node_weights().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["hat", "socks", "shoes", "pants", "shirt", "coat"]);
+
+        let lattice = parse(include_str!("../../gale-shapley-ex2.dot")).unwrap();
+        let blue = lattice.nodes.iter().filter(|n| n.attributes.get("color").is_some_and(|c| c == "blue"));
+        assert_eq!(blue.count(), 5);
+        let cities = parse(include_str!("../../paris-brussels-hague.dot")).unwrap();
+        assert_eq!(cities.attributes["rankdir"], "LR");
+        let hague = cities.find("Hague").unwrap();
+        assert_eq!(cities.edges[1], Edge { tail: 1, head: hague, attributes: [("label".into(), "180".into())].into() });
+    }
+
+    #[test]
+    fn attributes_and_chains() {
+        let source = r#"
+            strict digraph plan {
+                // Durations in days.
+                node [shape=box];
+                "design doc" [duration=3];
+                build [duration=5, label="Build it"]
+                /* Review and ship follow the build. */
+                "design doc" -> build -> ship [lag=1];
+            }
+        "#;
+        let graph = read_digraph(source).unwrap();
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph[NodeIndex::new(0)].name, "design doc");
+        assert_eq!(graph[NodeIndex::new(1)].attributes["label"], "Build it");
+        assert_eq!(graph.edge_count(), 2);
+        assert!(graph.edge_weights().all(|a| a["lag"] == "1"));
+    }
+
+    #[test]
+    fn subgraphs_and_defaults() {
+        let source = r#"
+            graph G {
+                edge [color=gray]
+                a -- {b c} [weight=2];
+                subgraph cluster_0 {
+                    label = "inside";
+                    node [shape=box]; edge [color=red]
+                    c -- d -- e
+                }
+                # A preprocessor line.
+                e -- f; subgraph cluster_0 { g }
+                {rank=same; f g} -- h:n:w
+            }
+        "#;
+        let dot = parse(source).unwrap();
+        assert_eq!(dot.name.as_deref(), Some("G"));
+        let names: Vec<_> = dot.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c", "d", "e", "f", "g", "h"]);
+        // Only the vertices created inside the cluster are boxes.
+        let boxes: Vec<_> = dot.nodes.iter().filter(|n| n.attributes.contains_key("shape")).map(|n| &n.name).collect();
+        assert_eq!(boxes, ["d", "e", "g"]);
+
+        let ends: Vec<_> = dot.edges.iter().map(|e| (names[e.tail], names[e.head], e.attributes["color"].as_str())).collect();
+        assert_eq!(
+            ends,
+            [
+                ("a", "b", "gray"), ("a", "c", "gray"), ("c", "d", "red"), ("d", "e", "red"), ("e", "f", "gray"),
+                ("f", "h", "gray"), ("g", "h", "gray"),
+            ]
+        );
+        assert_eq!(dot.edges[1].attributes["weight"], "2");
+        assert_eq!(dot.edges[6].attributes["headport"], "n:w");
+
+        assert_eq!(dot.subgraphs.len(), 3);
+        assert_eq!(dot.subgraphs[1].name.as_deref(), Some("cluster_0"));
+        assert_eq!(dot.subgraphs[1].attributes["label"], "inside");
+        assert_eq!(dot.subgraphs[1].nodes, [2, 3, 4, 6]);
+        assert_eq!(dot.subgraphs[2].attributes["rank"], "same");
+    }
+
+    #[test]
+    fn strict_graphs_merge_edges() {
+        let source = "STRICT Graph { a -- b [w=1]; b -- a [x=2]; a -- b -- a; \"graph\" -- -1.5 }";
+        let dot = parse(source).unwrap();
+        assert!(dot.strict);
+        assert_eq!(dot.edges.len(), 2);
+        assert_eq!(dot.edges[0].attributes, [("w".into(), "1".into()), ("x".into(), "2".into())].into());
+        assert_eq!(dot.nodes[2].name, "graph");
+        assert_eq!(dot.nodes[3].name, "-1.5");
+
+        let joined = parse(r#"digraph { "multi" + "ple\"quotes\"" -> b }"#).unwrap();
+        assert_eq!(joined.nodes[0].name, "multiple\"quotes\"");
+        let multigraph = parse("digraph { a -> b; a -> b }").unwrap();
+        assert_eq!(multigraph.edges.len(), 2);
+    }
+
+    #[test]
+    fn escaped_backslashes() {
+        let graph = parse(r#"digraph { a [label="C:\\"]; b [label="say \"hi\"\\\"" note="a\b"] }"#).unwrap();
+        assert_eq!(graph.nodes[0].attributes["label"], r"C:\\");
+        assert_eq!(graph.nodes[1].attributes["label"], r#"say "hi"\\""#);
+        assert_eq!(graph.nodes[1].attributes["note"], r"a\b");
+        assert_eq!(parse(r#"digraph { a [label="C:\"] }"#).unwrap_err().to_string(), "1:20: unterminated string");
+    }
+
+    #[test]
+    fn errors_have_positions() {
+        let error = read_digraph("digraph {\n  a -> ;\n}").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 8));
+        assert_eq!(error.to_string(), "2:8: expected an ID");
+        let error = read_digraph("graph { a -- b }").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 1));
+        let error = read_digraph("digraph { a -> b").unwrap_err();
+        assert_eq!(error.message, "expected '}'");
+        let errors = [
+            ("graph {\n  a -> b\n}", "2:5: '->' in an undirected graph, expected '--'"),
+            ("digraph { node }", "1:16: expected '['"),
+            ("digraph { a [label=<b>] }", "1:20: HTML strings aren't supported"),
+            ("digraph {\n  2a\n}", "2:3: unexpected 'a' after the number 2"),
+            ("digraph { a -> b } c", "1:20: unexpected text after the graph"),
+            ("digraph {\n  /* open", "2:10: unterminated comment"),
+            ("graph { subgraph { a -- b }", "1:28: expected '}'"),
+        ];
+        for (source, message) in errors {
+            assert_eq!(parse(source).unwrap_err().to_string(), message);
+        }
+    }
+}
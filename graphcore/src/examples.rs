@@ -0,0 +1,117 @@
+//! The small graphs the crates use in their examples and tests, so that each
+//! one is written down once. Vertices are named, and numbered in order of
+//! first mention like the DOT files at the top of the repository.
+
+use std::collections::HashMap;
+
+use petgraph::graph::{DiGraph, Graph, UnGraph};
+use petgraph::EdgeType;
+
+/// The map from `kingdom.dot`, with a road each way between neighbors and
+/// the travel time along it as the weight, as in rsgraph. Most places are
+/// joined by two roads of different lengths, and castle - treasure by two of
+/// the same length.
+pub fn kingdom() -> DiGraph<&'static str, f64> {
+    from_edges(&[], &[
+        ("start", "forest", 70.0), ("start", "mountains", 60.0), ("start", "sea", 54.0),
+        ("start", "city", 81.0), ("forest", "start", 42.0), ("forest", "mountains", 51.0),
+        ("forest", "desert", 56.0), ("forest", "cave", 63.0), ("mountains", "start", 71.0),
+        ("mountains", "forest", 38.0), ("mountains", "glacier", 72.0), ("desert", "forest", 93.0),
+        ("cave", "forest", 19.0), ("cave", "inferno", 17.0), ("inferno", "cave", 71.0),
+        ("glacier", "mountains", 25.0), ("sea", "start", 49.0), ("sea", "beach", 88.0),
+        ("beach", "sea", 79.0), ("beach", "city", 29.0), ("city", "beach", 30.0),
+        ("city", "start", 33.0), ("city", "castle", 36.0), ("castle", "city", 39.0),
+        ("castle", "treasure", 76.0), ("treasure", "castle", 76.0),
+    ])
+}
+
+/// `friends.dot`: twelve people and who is friends with whom.
+pub fn friends() -> UnGraph<&'static str, ()> {
+    from_edges(&[], &[
+        ("Frank", "Kim", ()), ("Frank", "David", ()), ("David", "Sven", ()), ("David", "Adin", ()),
+        ("Adin", "Maria", ()), ("Adin", "Jose", ()), ("Adin", "Kim", ()), ("Maria", "Jose", ()),
+        ("Jose", "Kim", ()), ("Jasmine", "Jose", ()), ("Jasmine", "Kim", ()), ("Ivan", "Adin", ()),
+        ("Jean", "Ivan", ()), ("Felix", "Ivan", ()), ("Kim", "Felix", ()), ("Kim", "David", ()),
+        ("Kim", "Jean", ()), ("Sven", "Adin", ()), ("Phil", "Adin", ()),
+    ])
+}
+
+/// The weighted example from the Neo4j GDS betweenness and closeness
+/// documentation, also built in rsgraph, with the vertices in the order
+/// GDS creates them. Nobody follows Alice or Bob, and Gale follows nobody.
+pub fn alice_gale() -> DiGraph<&'static str, f64> {
+    from_edges(&["Alice", "Bob", "Carol", "Dan", "Eve", "Frank", "Gale"], &[
+        ("Alice", "Carol", 1.0), ("Bob", "Carol", 1.0), ("Carol", "Dan", 1.0),
+        ("Carol", "Eve", 1.3), ("Dan", "Frank", 1.0), ("Eve", "Frank", 0.5),
+        ("Frank", "Gale", 1.0),
+    ])
+}
+
+/// a - b - c, d - e and an isolated f: a graph where closeness and
+/// harmonic centrality disagree.
+pub fn two_components() -> UnGraph<&'static str, ()> {
+    from_edges(&["a", "b", "c", "d", "e", "f"], &[("a", "b", ()), ("b", "c", ()), ("d", "e", ())])
+}
+
+/// A graph with the vertices in `nodes` and then any others named in
+/// `edges`, in order of first mention.
+fn from_edges<E: Clone, Ty: EdgeType>(
+    nodes: &[&'static str],
+    edges: &[(&'static str, &'static str, E)],
+) -> Graph<&'static str, E, Ty> {
+    let mut graph = Graph::default();
+    let mut index: HashMap<_, _> = nodes.iter().map(|&name| (name, graph.add_node(name))).collect();
+    for (u, v, w) in edges {
+        let u = *index.entry(*u).or_insert_with(|| graph.add_node(*u));
+        let v = *index.entry(*v).or_insert_with(|| graph.add_node(*v));
+        graph.add_edge(u, v, w.clone());
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    use crate::dot::parse;
+
+    /// Vertex names in order and edges as sets of names, ignoring
+    /// directions, weights and repeats.
+    fn skeleton<E, Ty: EdgeType>(graph: &Graph<&str, E, Ty>) -> (Vec<String>, BTreeSet<BTreeSet<String>>) {
+        let names = graph.node_weights().map(|n| n.to_string()).collect();
+        let edges = graph
+            .edge_indices()
+            .map(|e| {
+                let (u, v) = graph.edge_endpoints(e).unwrap();
+                BTreeSet::from([graph[u].to_string(), graph[v].to_string()])
+            })
+            .collect();
+        (names, edges)
+    }
+
+    fn dot_skeleton(source: &str) -> (Vec<String>, BTreeSet<BTreeSet<String>>) {
+        let dot = parse(source).unwrap();
+        let names = dot.nodes.iter().map(|n| n.name.clone()).collect();
+        let edges = dot
+            .edges
+            .iter()
+            .map(|e| BTreeSet::from([dot.nodes[e.tail].name.clone(), dot.nodes[e.head].name.clone()]))
+            .collect();
+        (names, edges)
+    }
+
+    #[test]
+    fn kingdom_matches_its_dot_file() {
+        let graph = kingdom();
+        assert_eq!(skeleton(&graph), dot_skeleton(include_str!("../../kingdom.dot")));
+        assert_eq!(graph.edge_count(), 26);
+    }
+
+    #[test]
+    fn friends_matches_its_dot_file() {
+        let graph = friends();
+        assert_eq!(skeleton(&graph), dot_skeleton(include_str!("../../friends.dot")));
+        assert_eq!(graph.edge_count(), 19);
+    }
+}
@@ -0,0 +1,334 @@
+//! Seeded random graphs: Erdős–Rényi G(n, p) and G(n, m), Barabási–Albert
+//! preferential attachment, Watts–Strogatz small worlds and random regular
+//! graphs.
+//!
+//! Vertex i holds the number i, and edges carry no weight. The same seed
+//! always gives the same graph. None of them have self-loops or parallel
+//! edges.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use petgraph::graph::{Graph, NodeIndex, UnGraph};
+use petgraph::EdgeType;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn with_nodes<Ty: EdgeType>(n: usize, edges: usize) -> Graph<usize, (), Ty> {
+    let mut graph = Graph::with_capacity(n, edges);
+    for i in 0..n {
+        graph.add_node(i);
+    }
+    graph
+}
+
+/// Number of vertices each vertex can have an edge to in row order: every
+/// other vertex for a directed graph, only the later ones for an undirected
+/// graph, so each pair comes up once.
+fn row_length(n: usize, u: usize, directed: bool) -> usize {
+    if directed { n - 1 } else { n - 1 - u }
+}
+
+/// The `k`th edge of row `u` in the order `row_length` counts them.
+fn row_edge(u: usize, k: usize, directed: bool) -> usize {
+    if !directed {
+        u + 1 + k
+    } else if k < u {
+        k
+    } else {
+        k + 1
+    }
+}
+
+/// Erdős–Rényi G(n, p): each of the possible edges independently with
+/// probability `p`. Directed graphs consider both directions between every
+/// pair. Batagelj and Brandes' geometric skipping makes this take time in
+/// proportion to n plus the number of edges rather than n².
+///
+/// Panics if `p` isn't between 0 and 1.
+pub fn gnp<Ty: EdgeType>(n: usize, p: f64, seed: u64) -> Graph<usize, (), Ty> {
+    assert!((0.0..=1.0).contains(&p), "p must be between 0 and 1");
+    let directed = Ty::is_directed();
+    let mut graph = with_nodes(n, 0);
+    if p == 0.0 {
+        return graph;
+    }
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let log_q = (1.0 - p).ln();
+    let (mut u, mut k): (usize, usize) = (0, 0);
+    while u < n {
+        // The number of edges to skip before the next one is geometric.
+        let r: f64 = rng.gen();
+        let skip = ((1.0 - r).ln() / log_q).floor();
+        k = k.saturating_add(skip as usize);
+        while u < n && k >= row_length(n, u, directed) {
+            k -= row_length(n, u, directed);
+            u += 1;
+        }
+        if u < n {
+            let v = row_edge(u, k, directed);
+            graph.add_edge(NodeIndex::new(u), NodeIndex::new(v), ());
+            k += 1;
+        }
+    }
+    graph
+}
+
+/// Erdős–Rényi G(n, m): exactly `m` edges, chosen uniformly from all the
+/// possible ones. Edges are added in row order.
+///
+/// Panics if there aren't `m` possible edges.
+pub fn gnm<Ty: EdgeType>(n: usize, m: usize, seed: u64) -> Graph<usize, (), Ty> {
+    let directed = Ty::is_directed();
+    let possible = if directed { n * n.saturating_sub(1) } else { n * n.saturating_sub(1) / 2 };
+    assert!(m <= possible, "a graph on {n} vertices has at most {possible} edges");
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut chosen = rand::seq::index::sample(&mut rng, possible, m).into_vec();
+    chosen.sort_unstable();
+
+    let mut graph = with_nodes(n, m);
+    let (mut u, mut row_start) = (0, 0);
+    for k in chosen {
+        while k >= row_start + row_length(n, u, directed) {
+            row_start += row_length(n, u, directed);
+            u += 1;
+        }
+        let v = row_edge(u, k - row_start, directed);
+        graph.add_edge(NodeIndex::new(u), NodeIndex::new(v), ());
+    }
+    graph
+}
+
+/// Barabási–Albert preferential attachment, as in networkx: start from a
+/// star on `m` + 1 vertices, then add vertices one at a time, each joined to
+/// `m` distinct earlier vertices picked with probability proportional to
+/// their degree. The degrees follow a power law with exponent 3.
+///
+/// Panics unless 1 <= `m` < `n`.
+pub fn barabasi_albert(n: usize, m: usize, seed: u64) -> UnGraph<usize, ()> {
+    assert!(m >= 1 && m < n, "need 1 <= m < n");
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut graph = with_nodes(n, m * (n - m));
+    // Each vertex appears once for every edge it has, so a uniform pick from
+    // here is a pick in proportion to degree.
+    let mut ends = Vec::with_capacity(2 * m * (n - m));
+    for v in 1..=m {
+        graph.add_edge(NodeIndex::new(0), NodeIndex::new(v), ());
+        ends.extend([0, v]);
+    }
+    for source in m + 1..n {
+        let mut targets = BTreeSet::new();
+        while targets.len() < m {
+            targets.insert(*ends.choose(&mut rng).unwrap());
+        }
+        for &target in &targets {
+            graph.add_edge(NodeIndex::new(source), NodeIndex::new(target), ());
+            ends.extend([source, target]);
+        }
+    }
+    graph
+}
+
+/// Watts–Strogatz small world: a ring where every vertex is joined to its
+/// `k` nearest neighbors, `k` / 2 on each side, after which each edge
+/// (u, u + j) keeps u and is rewired to a uniformly random new neighbor with
+/// probability `p`. Rewiring never creates a self-loop or a parallel edge,
+/// so the number of edges stays n k / 2.
+///
+/// Panics unless `k` is even and less than `n`, and `p` is between 0 and 1.
+pub fn watts_strogatz(n: usize, k: usize, p: f64, seed: u64) -> UnGraph<usize, ()> {
+    assert!(k.is_multiple_of(2) && k < n, "k must be even and less than n");
+    assert!((0.0..=1.0).contains(&p), "p must be between 0 and 1");
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut neighbors: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); n];
+    for u in 0..n {
+        for j in 1..=k / 2 {
+            let v = (u + j) % n;
+            neighbors[u].insert(v);
+            neighbors[v].insert(u);
+        }
+    }
+    // Rewire the nearest neighbors all the way round first, then the next
+    // nearest, and so on, as Watts and Strogatz describe.
+    for j in 1..=k / 2 {
+        for u in 0..n {
+            let v = (u + j) % n;
+            if neighbors[u].len() >= n - 1 || !rng.gen_bool(p) {
+                continue;
+            }
+            let mut w = rng.gen_range(0..n);
+            while w == u || neighbors[u].contains(&w) {
+                w = rng.gen_range(0..n);
+            }
+            neighbors[u].remove(&v);
+            neighbors[v].remove(&u);
+            neighbors[u].insert(w);
+            neighbors[w].insert(u);
+        }
+    }
+    from_neighbors(&neighbors)
+}
+
+/// A nearly uniformly random `d`-regular graph by Steger and Wormald's pairing
+/// algorithm: pair up d copies of every vertex at random, keep the pairs
+/// that make a new edge, and pair the leftovers again until none are left
+/// or no new edge is possible, in which case start over.
+///
+/// Panics unless `d` < `n` and n d is even.
+pub fn random_regular(n: usize, d: usize, seed: u64) -> UnGraph<usize, ()> {
+    assert!(d < n || n == 0, "d must be less than n");
+    assert!((n * d).is_multiple_of(2), "n d must be even");
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    loop {
+        if let Some(edges) = try_pairing(n, d, &mut rng) {
+            let mut neighbors = vec![BTreeSet::new(); n];
+            for (u, v) in edges {
+                neighbors[u].insert(v);
+                neighbors[v].insert(u);
+            }
+            return from_neighbors(&neighbors);
+        }
+    }
+}
+
+fn try_pairing(n: usize, d: usize, rng: &mut ChaCha8Rng) -> Option<HashSet<(usize, usize)>> {
+    let mut edges = HashSet::with_capacity(n * d / 2);
+    let mut points: Vec<usize> = (0..n).flat_map(|v| std::iter::repeat_n(v, d)).collect();
+    while !points.is_empty() {
+        points.shuffle(rng);
+        let mut leftover: HashMap<usize, usize> = HashMap::new();
+        for pair in points.chunks(2) {
+            let (u, v) = (pair[0].min(pair[1]), pair[0].max(pair[1]));
+            if u == v || !edges.insert((u, v)) {
+                *leftover.entry(u).or_default() += 1;
+                *leftover.entry(v).or_default() += 1;
+            }
+        }
+        let mut stuck: Vec<usize> = leftover.keys().copied().collect();
+        stuck.sort_unstable();
+        let possible = stuck
+            .iter()
+            .enumerate()
+            .any(|(i, &u)| stuck[i + 1..].iter().any(|&v| !edges.contains(&(u, v))));
+        if !stuck.is_empty() && !possible {
+            return None;
+        }
+        points = stuck.into_iter().flat_map(|v| std::iter::repeat_n(v, leftover[&v])).collect();
+    }
+    Some(edges)
+}
+
+/// An undirected graph with an edge u - v for each v > u in `neighbors[u]`,
+/// in order.
+fn from_neighbors(neighbors: &[BTreeSet<usize>]) -> UnGraph<usize, ()> {
+    let mut graph = with_nodes(neighbors.len(), 0);
+    for (u, adjacent) in neighbors.iter().enumerate() {
+        for &v in adjacent.range(u + 1..) {
+            graph.add_edge(NodeIndex::new(u), NodeIndex::new(v), ());
+        }
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::prelude::*;
+    use petgraph::visit::EdgeRef;
+
+    fn degrees<Ty: EdgeType>(graph: &Graph<usize, (), Ty>) -> Vec<usize> {
+        graph.node_indices().map(|v| graph.neighbors_undirected(v).count()).collect()
+    }
+
+    /// No self-loops and no two edges between the same ordered pair, or the
+    /// same pair either way round when undirected.
+    fn is_simple<Ty: EdgeType>(graph: &Graph<usize, (), Ty>) -> bool {
+        let mut seen = HashSet::new();
+        graph.edge_references().all(|e| {
+            let (u, v) = (e.source().index(), e.target().index());
+            let key = if Ty::is_directed() { (u, v) } else { (u.min(v), u.max(v)) };
+            u != v && seen.insert(key)
+        })
+    }
+
+    fn edges<Ty: EdgeType>(graph: &Graph<usize, (), Ty>) -> Vec<(usize, usize)> {
+        graph.edge_references().map(|e| (e.source().index(), e.target().index())).collect()
+    }
+
+    #[test]
+    fn erdos_renyi() {
+        let graph: UnGraph<_, _> = gnp(1000, 0.01, 1);
+        assert_eq!(graph.node_count(), 1000);
+        assert!(is_simple(&graph));
+        // 4995 expected, with a standard deviation of about 70.
+        assert!((4700..5300).contains(&graph.edge_count()), "{}", graph.edge_count());
+        let directed: DiGraph<_, _> = gnp(300, 0.05, 1);
+        assert!(is_simple(&directed));
+        assert!((4000..4970).contains(&directed.edge_count()), "{}", directed.edge_count());
+
+        assert_eq!(gnp::<Undirected>(10, 0.0, 1).edge_count(), 0);
+        assert_eq!(gnp::<Undirected>(10, 1.0, 1).edge_count(), 45);
+        assert_eq!(gnp::<Directed>(10, 1.0, 1).edge_count(), 90);
+
+        for (n, m) in [(1000, 5000), (10, 45), (0, 0)] {
+            let graph: UnGraph<_, _> = gnm(n, m, 2);
+            assert_eq!(graph.edge_count(), m);
+            assert!(is_simple(&graph));
+        }
+        let directed: DiGraph<_, _> = gnm(10, 90, 2);
+        assert!(is_simple(&directed));
+        assert_eq!(directed.edge_count(), 90);
+    }
+
+    #[test]
+    fn same_seed_same_graph() {
+        assert_eq!(edges(&gnp::<Directed>(50, 0.1, 7)), edges(&gnp::<Directed>(50, 0.1, 7)));
+        assert_ne!(edges(&gnp::<Directed>(50, 0.1, 7)), edges(&gnp::<Directed>(50, 0.1, 8)));
+        assert_eq!(edges(&gnm::<Undirected>(50, 100, 7)), edges(&gnm::<Undirected>(50, 100, 7)));
+        assert_eq!(edges(&barabasi_albert(50, 2, 7)), edges(&barabasi_albert(50, 2, 7)));
+        assert_eq!(edges(&watts_strogatz(50, 4, 0.2, 7)), edges(&watts_strogatz(50, 4, 0.2, 7)));
+        assert_eq!(edges(&random_regular(50, 3, 7)), edges(&random_regular(50, 3, 7)));
+    }
+
+    #[test]
+    fn preferential_attachment() {
+        let (n, m) = (2000, 3);
+        let graph = barabasi_albert(n, m, 5);
+        assert!(is_simple(&graph));
+        // The star's m edges, then m for every later vertex.
+        assert_eq!(graph.edge_count(), m + (n - m - 1) * m);
+        let degree = degrees(&graph);
+        assert!(degree.iter().all(|&d| d >= m));
+        // A heavy tail: a few hubs far above the mean degree of about 6.
+        assert!(*degree.iter().max().unwrap() > 60);
+        assert!(crate::connectivity::Connectivity::of(&graph).is_connected());
+    }
+
+    #[test]
+    fn small_world() {
+        let ring = watts_strogatz(20, 4, 0.0, 3);
+        assert_eq!(ring.edge_count(), 40);
+        assert!(degrees(&ring).iter().all(|&d| d == 4));
+        assert!(ring.find_edge(NodeIndex::new(19), NodeIndex::new(1)).is_some());
+
+        for p in [0.1, 0.5, 1.0] {
+            let graph = watts_strogatz(200, 6, p, 3);
+            assert!(is_simple(&graph));
+            assert_eq!(graph.edge_count(), 600);
+        }
+        // A few shortcuts shrink the distances a lot.
+        let hops = |graph: &UnGraph<usize, ()>| crate::distance::Distances::new(graph, |_| 1.0).average_path_length();
+        let ring = watts_strogatz(200, 6, 0.0, 3);
+        let small_world = watts_strogatz(200, 6, 0.1, 3);
+        assert!(hops(&small_world) < hops(&ring) / 2.0);
+    }
+
+    #[test]
+    fn regular() {
+        for (n, d) in [(10, 3), (100, 5), (50, 49), (6, 0)] {
+            let graph = random_regular(n, d, 11);
+            assert!(is_simple(&graph));
+            assert!(degrees(&graph).iter().all(|&x| x == d), "n = {n}, d = {d}");
+        }
+    }
+}
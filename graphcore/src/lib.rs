@@ -0,0 +1,17 @@
+//! Graph algorithms shared by the `betweenness`, `closeness`, `graphstats`
+//! and `rsgraph` crates.
+
+pub mod community;
+pub mod connectivity;
+pub mod dag;
+pub mod degree;
+pub mod distance;
+pub mod dot;
+pub mod examples;
+pub mod generators;
+pub mod pagerank;
+pub mod paths;
+pub mod scores;
+pub mod spanning;
+pub mod spectral;
+pub mod trace;
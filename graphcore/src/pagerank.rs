@@ -0,0 +1,216 @@
+//! PageRank by power iteration. The defaults and the handling of dangling
+//! vertices follow networkx's `pagerank`.
+
+use petgraph::graph::{EdgeReference, Graph};
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+
+use crate::scores::Scores;
+
+/// How a power iteration finished.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Convergence {
+    pub iterations: usize,
+    /// L1 distance between the last two iterations.
+    pub residual: f64,
+    pub converged: bool,
+}
+
+/// Where the rank of a vertex without outgoing edges goes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dangling {
+    /// Spread it like a teleport, using the personalization vector if there
+    /// is one. This is what networkx does.
+    #[default]
+    Teleport,
+    /// Spread it evenly over every vertex, regardless of personalization.
+    Uniform,
+}
+
+#[derive(Clone, Debug)]
+pub struct PageRank {
+    /// Probability of following an edge instead of teleporting.
+    pub damping: f64,
+    /// Stop once the L1 distance between successive iterations is smaller
+    /// than this.
+    pub tolerance: f64,
+    pub max_iterations: usize,
+    /// Teleport weights indexed by `NodeIndex::index()`. They are scaled to
+    /// sum to one, so they must not be negative or all zero. `None`
+    /// teleports uniformly.
+    pub personalization: Option<Vec<f64>>,
+    pub dangling: Dangling,
+}
+
+impl Default for PageRank {
+    fn default() -> Self {
+        PageRank {
+            damping: 0.85,
+            tolerance: 1e-6,
+            max_iterations: 100,
+            personalization: None,
+            dangling: Dangling::default(),
+        }
+    }
+}
+
+/// PageRank of every vertex, highest first. Each vertex passes its rank to
+/// its out-neighbors in proportion to `edge_weight` (`|_| 1.0` ignores
+/// weights); undirected edges count in both directions. Panics if the
+/// personalization has a negative weight or sums to zero.
+pub fn pagerank<N, E, Ty, F>(graph: &Graph<N, E, Ty>, edge_weight: F, options: &PageRank) -> (Scores<N>, Convergence)
+where
+    N: Clone,
+    Ty: EdgeType,
+    F: Fn(EdgeReference<E>) -> f64,
+{
+    let n = graph.node_count();
+    let alpha = options.damping;
+    let teleport = match &options.personalization {
+        Some(p) => {
+            assert_eq!(p.len(), n, "one personalization weight per vertex");
+            assert!(p.iter().all(|&x| x >= 0.0), "personalization weights must not be negative");
+            let total: f64 = p.iter().sum();
+            assert!(total > 0.0, "personalization must not be all zero");
+            p.iter().map(|x| x / total).collect()
+        }
+        None => vec![1.0 / n as f64; n],
+    };
+    let dangling_weights = match options.dangling {
+        Dangling::Teleport => teleport.clone(),
+        Dangling::Uniform => vec![1.0 / n as f64; n],
+    };
+    let out_weight: Vec<f64> = graph
+        .node_indices()
+        .map(|v| graph.edges(v).map(&edge_weight).sum())
+        .collect();
+
+    let mut x = vec![1.0 / n as f64; n];
+    let mut convergence = Convergence { iterations: 0, residual: f64::INFINITY, converged: n == 0 };
+    while !convergence.converged && convergence.iterations < options.max_iterations {
+        let last = x;
+        x = vec![0.0; n];
+        let dangling_sum: f64 = graph
+            .node_indices()
+            .filter(|v| out_weight[v.index()] == 0.0)
+            .map(|v| last[v.index()])
+            .sum::<f64>()
+            * alpha;
+        for v in graph.node_indices() {
+            let i = v.index();
+            if out_weight[i] > 0.0 {
+                for edge in graph.edges(v) {
+                    x[edge.target().index()] += alpha * last[i] * edge_weight(edge) / out_weight[i];
+                }
+            }
+            x[i] += dangling_sum * dangling_weights[i] + (1.0 - alpha) * teleport[i];
+        }
+        convergence.iterations += 1;
+        convergence.residual = x.iter().zip(&last).map(|(a, b)| (a - b).abs()).sum();
+        convergence.converged = convergence.residual < options.tolerance;
+    }
+
+    let mut scores = Scores::new(graph, x);
+    scores.sort_descending();
+    (scores, convergence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::{alice_gale, friends};
+
+    fn assert_ranks<E, Ty: EdgeType>(graph: &Graph<&str, E, Ty>, scores: &Scores<&str>, expected: &[(&str, f64)]) {
+        assert_eq!(scores.len(), graph.node_count());
+        for (name, x) in expected {
+            let actual = scores.get_by_weight(name).unwrap();
+            assert!((actual - x).abs() < 1e-9, "{name}: {actual} != {x}");
+        }
+        let order: Vec<_> = scores.iter().map(|e| e.score).collect();
+        assert!(order.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    fn tight() -> PageRank {
+        PageRank { tolerance: 1e-13, max_iterations: 1000, ..Default::default() }
+    }
+
+    #[test]
+    fn networkx_friends() {
+        // nx.pagerank(G) on the friends graph.
+        let graph = friends();
+        let (scores, convergence) = pagerank(&graph, |_| 1.0, &tight());
+        assert!(convergence.converged);
+        assert_ranks(&graph, &scores, &[
+            ("Adin", 0.1755676003), ("Kim", 0.1717637074), ("David", 0.1015721995),
+            ("Jose", 0.1015721995), ("Ivan", 0.0818955758), ("Jean", 0.0565607681),
+            ("Felix", 0.0565607681), ("Maria", 0.0554030153), ("Sven", 0.0554030153),
+            ("Frank", 0.0549411140), ("Jasmine", 0.0549411140), ("Phil", 0.0338189229),
+        ]);
+        assert_eq!(scores.iter().next().unwrap().weight, "Adin");
+    }
+
+    #[test]
+    fn networkx_personalized() {
+        // nx.pagerank(G, personalization={"Phil": 1})
+        let graph = friends();
+        let phil = graph.node_indices().find(|&v| graph[v] == "Phil").unwrap();
+        let mut p = vec![0.0; graph.node_count()];
+        p[phil.index()] = 1.0;
+        let options = PageRank { personalization: Some(p), ..tight() };
+        let (scores, _) = pagerank(&graph, |_| 1.0, &options);
+        assert_ranks(&graph, &scores, &[
+            ("Adin", 0.2604967475), ("Phil", 0.1816317479), ("Kim", 0.1184690332),
+            ("Jean", 0.0307545827), ("Felix", 0.0307545827),
+        ]);
+    }
+
+    #[test]
+    fn networkx_weighted_dangling() {
+        // Gale has no outgoing edges. nx.pagerank(D, weight="weight")
+        let graph = alice_gale();
+        let (scores, _) = pagerank(&graph, |e| *e.weight(), &tight());
+        assert_ranks(&graph, &scores, &[
+            ("Alice", 0.0537666567), ("Bob", 0.0537666567), ("Carol", 0.1451699732),
+            ("Dan", 0.1074164294), ("Eve", 0.1235113613), ("Frank", 0.2500552788),
+            ("Gale", 0.2663136438),
+        ]);
+
+        // personalization={"Alice": 1}, with and without dangling={v: 1 for v in D}
+        let mut p = vec![0.0; 7];
+        p[0] = 1.0;
+        let options = PageRank { personalization: Some(p), ..tight() };
+        let (scores, _) = pagerank(&graph, |e| *e.weight(), &options);
+        assert_ranks(&graph, &scores, &[("Alice", 0.2696412592), ("Bob", 0.0), ("Gale", 0.1407544225)]);
+        let options = PageRank { dangling: Dangling::Uniform, ..options };
+        let (scores, _) = pagerank(&graph, |e| *e.weight(), &options);
+        assert_ranks(&graph, &scores, &[("Alice", 0.1738565512), ("Bob", 0.0238565512), ("Gale", 0.196465716)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be negative")]
+    fn negative_personalization() {
+        let graph = friends();
+        let mut p = vec![1.0; graph.node_count()];
+        p[0] = -1.0;
+        pagerank(&graph, |_| 1.0, &PageRank { personalization: Some(p), ..Default::default() });
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be all zero")]
+    fn zero_personalization() {
+        let graph = friends();
+        let p = vec![0.0; graph.node_count()];
+        pagerank(&graph, |_| 1.0, &PageRank { personalization: Some(p), ..Default::default() });
+    }
+
+    #[test]
+    fn iteration_cap() {
+        let graph = friends();
+        let options = PageRank { max_iterations: 3, ..Default::default() };
+        let (scores, convergence) = pagerank(&graph, |_| 1.0, &options);
+        assert_eq!(convergence.iterations, 3);
+        assert!(!convergence.converged);
+        // Still a probability distribution.
+        assert!((scores.iter().map(|e| e.score).sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+}
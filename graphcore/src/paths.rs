@@ -0,0 +1,390 @@
+//! Single-source shortest paths from every vertex, spread across threads with
+//! rayon.
+//!
+//! Each source runs Dijkstra's algorithm and also counts the shortest paths
+//! to every vertex (sigma) and remembers their predecessors, which is
+//! everything Brandes' betweenness needs. Closeness and average path length
+//! only need the distances. The per-source results are merged in
+//! source order on the calling thread, so floating-point sums come out
+//! bit-for-bit the same as a serial loop over `graph.node_indices()`.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use num_traits::{Num, Zero};
+use petgraph::graph::{EdgeReference, Graph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+use rayon::prelude::*;
+
+use crate::distance;
+
+/// Shortest paths from one source, with path counts of type `T` and
+/// distances of type `D`. Distances are sums of edge costs in `D` itself, so
+/// `f32` weights tie exactly when their `f32` sums do.
+#[derive(Clone, Debug)]
+pub struct ShortestPaths<T, D = f64> {
+    pub source: NodeIndex,
+    /// Reachable vertices in the order they were settled, which is
+    /// non-decreasing distance from the source. The source comes first.
+    pub order: Vec<NodeIndex>,
+    /// Distance to each vertex, `None` if it can't be reached.
+    pub dist: Vec<Option<D>>,
+    /// Number of shortest paths to each vertex.
+    pub sigma: Vec<T>,
+    /// Every vertex that immediately precedes this one on a shortest path.
+    pub pred: Vec<Vec<NodeIndex>>,
+}
+
+impl<T: Clone + Num, D: Copy + PartialOrd + Zero> ShortestPaths<T, D> {
+    /// Dijkstra's algorithm from `source`, keeping every tied path. Edge costs
+    /// must not be negative.
+    pub fn from_source<N, E, Ty, F>(graph: &Graph<N, E, Ty>, source: NodeIndex, edge_cost: F) -> Self
+    where
+        Ty: EdgeType,
+        F: Fn(EdgeReference<E>) -> D,
+    {
+        let n = graph.node_count();
+        let mut order = Vec::with_capacity(n);
+        let mut pred: Vec<Vec<NodeIndex>> = vec![Vec::new(); n];
+        let mut sigma = vec![T::zero(); n];
+        let mut dist: Vec<Option<D>> = vec![None; n];
+        let mut settled = vec![false; n];
+        let mut queue = BinaryHeap::new();
+
+        sigma[source.index()] = T::one();
+        dist[source.index()] = Some(D::zero());
+        queue.push(MinScored(D::zero(), source));
+        while let Some(MinScored(d, v)) = queue.pop() {
+            if settled[v.index()] {
+                continue;
+            }
+            settled[v.index()] = true;
+            order.push(v);
+            for edge in graph.edges(v) {
+                let w = edge.target();
+                let alt = d + edge_cost(edge);
+                match dist[w.index()] {
+                    Some(dw) if alt == dw => {
+                        // Another shortest path to w, this time through v.
+                        sigma[w.index()] = sigma[w.index()].clone() + sigma[v.index()].clone();
+                        pred[w.index()].push(v);
+                    }
+                    Some(dw) if alt > dw => (),
+                    _ => {
+                        dist[w.index()] = Some(alt);
+                        sigma[w.index()] = sigma[v.index()].clone();
+                        pred[w.index()] = vec![v];
+                        queue.push(MinScored(alt, w));
+                    }
+                }
+            }
+        }
+        ShortestPaths { source, order, dist, sigma, pred }
+    }
+
+    /// The dependency of every vertex on the source: the (fractional) number
+    /// of shortest paths from the source that pass through it. Summing this
+    /// over all sources gives the raw betweenness. With `endpoints`, the
+    /// source and target of each path count as lying on it.
+    pub fn dependencies(&self, endpoints: bool) -> Vec<T> {
+        let n = self.dist.len();
+        let mut delta = vec![T::zero(); n];
+        let mut result = vec![T::zero(); n];
+        if endpoints {
+            result[self.source.index()] = (0..self.reached()).fold(T::zero(), |x, _| x + T::one());
+        }
+        // Farthest vertices first.
+        for &w in self.order.iter().rev() {
+            let dw = delta[w.index()].clone() + T::one();
+            for &v in &self.pred[w.index()] {
+                let share = self.sigma[v.index()].clone() / self.sigma[w.index()].clone();
+                delta[v.index()] = delta[v.index()].clone() + share * dw.clone();
+            }
+            if w != self.source {
+                result[w.index()] = delta[w.index()].clone();
+                if endpoints {
+                    result[w.index()] = result[w.index()].clone() + T::one();
+                }
+            }
+        }
+        result
+    }
+
+    /// The dependency of every shortest-path edge on the source, as
+    /// (v, w, share) for each predecessor v of w. Summing the shares of an
+    /// edge over all sources gives its raw edge betweenness.
+    pub fn edge_dependencies(&self) -> Vec<(NodeIndex, NodeIndex, T)> {
+        let mut delta = vec![T::zero(); self.dist.len()];
+        let mut result = Vec::new();
+        for &w in self.order.iter().rev() {
+            let dw = delta[w.index()].clone() + T::one();
+            for &v in &self.pred[w.index()] {
+                let share = self.sigma[v.index()].clone() / self.sigma[w.index()].clone() * dw.clone();
+                delta[v.index()] = delta[v.index()].clone() + share.clone();
+                result.push((v, w, share));
+            }
+        }
+        result
+    }
+}
+
+impl<T, D> ShortestPaths<T, D> {
+    /// Number of vertices reachable from the source, not counting itself.
+    pub fn reached(&self) -> usize {
+        self.order.len() - 1
+    }
+}
+
+impl<T, D: Copy + Into<f64>> ShortestPaths<T, D> {
+    /// Sum of the distances to every reachable vertex.
+    pub fn total_distance(&self) -> f64 {
+        self.order.iter().filter_map(|v| self.dist[v.index()]).map(Into::into).sum()
+    }
+
+    /// Distance to the farthest vertex, infinite if some vertex can't be
+    /// reached.
+    pub fn eccentricity(&self) -> f64 {
+        if self.order.len() < self.dist.len() {
+            return f64::INFINITY;
+        }
+        self.order.last().and_then(|v| self.dist[v.index()]).map_or(0.0, Into::into)
+    }
+}
+
+/// Compute the shortest paths from every source in parallel, `map` each one
+/// to whatever the caller needs, and hand those to `merge` one at a time in
+/// source order.
+pub fn for_each_source<N, E, Ty, T, D, F, M, R>(
+    graph: &Graph<N, E, Ty>,
+    edge_cost: F,
+    map: M,
+    merge: impl FnMut(R),
+) where
+    N: Sync,
+    E: Sync,
+    Ty: EdgeType + Sync,
+    T: Clone + Num,
+    D: Copy + PartialOrd + Zero,
+    F: Fn(EdgeReference<E>) -> D + Sync,
+    M: Fn(ShortestPaths<T, D>) -> R + Sync,
+    R: Send,
+{
+    let sources: Vec<NodeIndex> = graph.node_indices().collect();
+    for_each_of(graph, &sources, edge_cost, map, merge);
+}
+
+/// `for_each_source` for just the given sources, such as a sample of
+/// pivots. A source listed twice is computed twice.
+pub fn for_each_of<N, E, Ty, T, D, F, M, R>(
+    graph: &Graph<N, E, Ty>,
+    sources: &[NodeIndex],
+    edge_cost: F,
+    map: M,
+    mut merge: impl FnMut(R),
+) where
+    N: Sync,
+    E: Sync,
+    Ty: EdgeType + Sync,
+    T: Clone + Num,
+    D: Copy + PartialOrd + Zero,
+    F: Fn(EdgeReference<E>) -> D + Sync,
+    M: Fn(ShortestPaths<T, D>) -> R + Sync,
+    R: Send,
+{
+    // Enough sources per batch to keep every thread busy without holding all
+    // n results in memory at once.
+    let batch = 64 * rayon::current_num_threads();
+    for chunk in sources.chunks(batch) {
+        let results: Vec<R> = chunk
+            .par_iter()
+            .map(|&s| map(ShortestPaths::from_source(graph, s, &edge_cost)))
+            .collect();
+        results.into_iter().for_each(&mut merge);
+    }
+}
+
+/// Everything we can learn from one all-sources traversal, indexed by
+/// `NodeIndex::index()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathSummary {
+    /// Reachable vertices divided by the sum of the distances to them, which
+    /// is (n - 1) / farness in a connected graph, or zero if nothing is
+    /// reachable.
+    pub closeness: Vec<f64>,
+    /// Raw Brandes betweenness. Undirected graphs count each pair once.
+    pub betweenness: Vec<f64>,
+    /// Mean distance over all ordered pairs of distinct vertices where the
+    /// second is reachable from the first. Unlike
+    /// `Distances::average_path_length` this stays finite on a graph that
+    /// isn't strongly connected.
+    pub average_reachable_path_length: f64,
+    /// Distance from each vertex to the one farthest from it, infinite if it
+    /// can't reach them all, as in `Distances::eccentricity`.
+    pub eccentricity: Vec<f64>,
+}
+
+impl PathSummary {
+    pub fn diameter(&self) -> f64 {
+        distance::diameter(&self.eccentricity)
+    }
+
+    pub fn radius(&self) -> f64 {
+        distance::radius(&self.eccentricity)
+    }
+
+    pub fn center(&self) -> Vec<NodeIndex> {
+        distance::center(&self.eccentricity)
+    }
+
+    pub fn periphery(&self) -> Vec<NodeIndex> {
+        distance::periphery(&self.eccentricity)
+    }
+}
+
+/// Closeness, betweenness, average path length and eccentricity from a single
+/// traversal, so none of them has to run Dijkstra from every vertex again.
+pub fn path_summary<N, E, Ty, F>(graph: &Graph<N, E, Ty>, edge_cost: F) -> PathSummary
+where
+    N: Sync,
+    E: Sync,
+    Ty: EdgeType + Sync,
+    F: Fn(EdgeReference<E>) -> f64 + Sync,
+{
+    let n = graph.node_count();
+    let mut summary = PathSummary {
+        closeness: Vec::with_capacity(n),
+        betweenness: vec![0.0; n],
+        average_reachable_path_length: 0.0,
+        eccentricity: Vec::with_capacity(n),
+    };
+    let mut pairs = 0;
+    let mut total = 0.0;
+    for_each_source(
+        graph,
+        edge_cost,
+        |sp: ShortestPaths<f64>| {
+            (sp.reached(), sp.total_distance(), sp.eccentricity(), sp.dependencies(false))
+        },
+        |(reached, distance, eccentricity, delta)| {
+            summary.eccentricity.push(eccentricity);
+            summary.closeness.push(if distance > 0.0 { reached as f64 / distance } else { 0.0 });
+            for (x, d) in summary.betweenness.iter_mut().zip(delta) {
+                *x += d;
+            }
+            pairs += reached;
+            total += distance;
+        },
+    );
+    if !graph.is_directed() {
+        summary.betweenness.iter_mut().for_each(|x| *x /= 2.0);
+    }
+    summary.average_reachable_path_length = total / pairs as f64;
+    summary
+}
+
+/// Priority queue entry ordered by its score alone, so that `BinaryHeap`
+/// pops the smallest score first. Break ties by putting them in the score,
+/// like `(weight, index)`. Incomparable scores, like NaN, count as equal.
+pub(crate) struct MinScored<K, T>(pub(crate) K, pub(crate) T);
+
+impl<K: PartialOrd, T> PartialEq for MinScored<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: PartialOrd, T> Eq for MinScored<K, T> {}
+
+impl<K: PartialOrd, T> PartialOrd for MinScored<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: PartialOrd, T> Ord for MinScored<K, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.partial_cmp(&self.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::prelude::*;
+
+    /// A few hundred vertices with irregular weights, so that the order of
+    /// floating-point additions matters.
+    fn tangle() -> DiGraph<(), f64> {
+        let n = 300;
+        let mut graph = DiGraph::new();
+        let v: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+        for i in 0..n {
+            for k in [1, 7, 31] {
+                let w = ((i * 37 + k * 11) % 13) as f64 / 3.0 + 0.1;
+                graph.add_edge(v[i], v[(i + k) % n], w);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn parallel_matches_serial() {
+        let graph = tangle();
+        let summary = path_summary(&graph, |e| *e.weight());
+
+        let mut betweenness = vec![0.0; graph.node_count()];
+        let mut closeness = Vec::new();
+        for s in graph.node_indices() {
+            let sp: ShortestPaths<f64> = ShortestPaths::from_source(&graph, s, |e| *e.weight());
+            for (x, d) in betweenness.iter_mut().zip(sp.dependencies(false)) {
+                *x += d;
+            }
+            closeness.push(sp.reached() as f64 / sp.total_distance());
+        }
+        let bits = |v: &[f64]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&summary.betweenness), bits(&betweenness));
+        assert_eq!(bits(&summary.closeness), bits(&closeness));
+    }
+
+    #[test]
+    fn path_graph() {
+        // a - b - c - d - e
+        let mut graph: UnGraph<(), ()> = Graph::new_undirected();
+        let v: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+        graph.extend_with_edges(v.windows(2).map(|w| (w[0], w[1])));
+        let summary = path_summary(&graph, |_| 1.0);
+        assert_eq!(summary.closeness, [0.4, 4.0 / 7.0, 4.0 / 6.0, 4.0 / 7.0, 0.4]);
+        assert_eq!(summary.betweenness, [0.0, 3.0, 4.0, 3.0, 0.0]);
+        assert_eq!(summary.average_reachable_path_length, 2.0);
+        assert_eq!(summary.eccentricity, [4.0, 3.0, 2.0, 3.0, 4.0]);
+        assert_eq!((summary.diameter(), summary.radius()), (4.0, 2.0));
+        assert_eq!(summary.center(), [NodeIndex::new(2)]);
+        assert_eq!(summary.periphery(), [NodeIndex::new(0), NodeIndex::new(4)]);
+    }
+
+    #[test]
+    fn edge_dependencies() {
+        // Two shortest paths from a to d, through b and through c.
+        let mut graph: UnGraph<(), ()> = Graph::new_undirected();
+        let v: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.extend_with_edges([(v[0], v[1]), (v[0], v[2]), (v[1], v[3]), (v[2], v[3])]);
+        let sp: ShortestPaths<f64> = ShortestPaths::from_source(&graph, v[0], |_| 1.0);
+        let mut shares = sp.edge_dependencies();
+        shares.sort_by_key(|&(a, b, _)| (a, b));
+        assert_eq!(shares, [(v[0], v[1], 1.5), (v[0], v[2], 1.5), (v[1], v[3], 0.5), (v[2], v[3], 0.5)]);
+    }
+
+    #[test]
+    fn unreachable() {
+        // b can't reach a, so only a -> b counts towards the average.
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, ());
+        let summary = path_summary(&graph, |_| 1.0);
+        assert_eq!(summary.closeness, [1.0, 0.0]);
+        assert_eq!(summary.average_reachable_path_length, 1.0);
+        assert_eq!(summary.eccentricity, [1.0, f64::INFINITY]);
+    }
+}
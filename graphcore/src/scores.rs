@@ -0,0 +1,314 @@
+//! Per-vertex results that remember which vertex each score belongs to, and a
+//! formatter to print them as a table, CSV or JSON.
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Write};
+use std::ops::Index;
+use std::str::FromStr;
+
+use petgraph::graph::{Graph, NodeIndex};
+use petgraph::EdgeType;
+
+/// One row of a `Scores`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry<N, S> {
+    pub node: NodeIndex,
+    /// The vertex's weight in the graph, usually its name.
+    pub weight: N,
+    pub score: S,
+}
+
+/// A score for every vertex of a graph, keyed by `NodeIndex` and by vertex
+/// weight. Entries start out in node index order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scores<N, S = f64> {
+    entries: Vec<Entry<N, S>>,
+    /// Where each node index lives in `entries`.
+    position: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned columns for reading in a terminal.
+    #[default]
+    Table,
+    Csv,
+    /// An array of objects, one per vertex.
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "table" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format {s:?}, expected table, csv or json")),
+        }
+    }
+}
+
+impl Format {
+    /// The format named by a command line argument, or a table without one.
+    /// Anything else prints the error and `usage` and exits with status 2.
+    pub fn from_arg(arg: Option<&str>, usage: &str) -> Format {
+        match arg.map(str::parse) {
+            None => Format::Table,
+            Some(Ok(format)) => format,
+            Some(Err(e)) => {
+                eprintln!("{e}\n{usage}");
+                std::process::exit(2);
+            }
+        }
+    }
+}
+
+impl<N: Clone, S> Scores<N, S> {
+    /// Pair each vertex of `graph`, in node index order, with its score.
+    pub fn new<E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, scores: impl IntoIterator<Item = S>) -> Self {
+        let entries: Vec<_> = graph
+            .node_indices()
+            .zip(scores)
+            .map(|(node, score)| Entry { node, weight: graph[node].clone(), score })
+            .collect();
+        assert_eq!(entries.len(), graph.node_count(), "one score per vertex");
+        let position = (0..entries.len()).collect();
+        Scores { entries, position }
+    }
+}
+
+impl<N, S> Scores<N, S> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, node: NodeIndex) -> Option<&S> {
+        let i = *self.position.get(node.index())?;
+        Some(&self.entries[i].score)
+    }
+
+    /// The score of the first vertex whose weight is `weight`.
+    pub fn get_by_weight(&self, weight: &N) -> Option<&S>
+    where
+        N: PartialEq,
+    {
+        self.entries.iter().find(|e| &e.weight == weight).map(|e| &e.score)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entry<N, S>> {
+        self.entries.iter()
+    }
+
+    /// Reorder the entries. Lookups by node index still work afterwards.
+    pub fn sort_by(&mut self, compare: impl FnMut(&Entry<N, S>, &Entry<N, S>) -> Ordering) {
+        self.entries.sort_by(compare);
+        for (i, e) in self.entries.iter().enumerate() {
+            self.position[e.node.index()] = i;
+        }
+    }
+
+    /// Highest score first. Ties keep their current order.
+    pub fn sort_descending(&mut self)
+    where
+        S: PartialOrd,
+    {
+        self.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    }
+
+    /// Apply `f` to every score.
+    pub fn map<T>(self, mut f: impl FnMut(S) -> T) -> Scores<N, T> {
+        Scores {
+            entries: self
+                .entries
+                .into_iter()
+                .map(|Entry { node, weight, score }| Entry { node, weight, score: f(score) })
+                .collect(),
+            position: self.position,
+        }
+    }
+
+    /// Render the scores in `format`, calling the score column `column`.
+    pub fn render(&self, format: Format, column: &str) -> String
+    where
+        N: Display,
+        S: Display,
+    {
+        let rows: Vec<[String; 3]> = self
+            .entries
+            .iter()
+            .map(|e| [e.node.index().to_string(), e.weight.to_string(), e.score.to_string()])
+            .collect();
+        let header = [("node", Column::Number), ("name", Column::Text), (column, Column::Number)];
+        render_rows(format, header, &rows)
+    }
+}
+
+/// How a column is aligned in a table and written in JSON.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Column {
+    /// Left-aligned, always a JSON string.
+    Text,
+    /// Right-aligned, a JSON number when it parses as a finite one.
+    Number,
+}
+
+/// Render `rows` in `format` under `header`, which names each column and
+/// says what it holds.
+pub(crate) fn render_rows<const K: usize>(format: Format, header: [(&str, Column); K], rows: &[[String; K]]) -> String {
+    let mut out = String::new();
+    match format {
+        Format::Table => {
+            let mut width = header.map(|(name, _)| name.chars().count());
+            for row in rows {
+                for (w, cell) in width.iter_mut().zip(row) {
+                    *w = (*w).max(cell.chars().count());
+                }
+            }
+            let names = header.map(|(name, _)| name.to_string());
+            for row in std::iter::once(&names).chain(rows) {
+                let cells: Vec<_> = row
+                    .iter()
+                    .zip(header)
+                    .zip(width)
+                    .map(|((cell, (_, column)), w)| match column {
+                        Column::Text => format!("{cell:<w$}"),
+                        Column::Number => format!("{cell:>w$}"),
+                    })
+                    .collect();
+                writeln!(out, "{}", cells.join("  ").trim_end()).unwrap();
+            }
+        }
+        Format::Csv => {
+            let names = header.map(|(name, _)| name.to_string());
+            for row in std::iter::once(&names).chain(rows) {
+                let cells: Vec<_> = row.iter().map(|c| csv_field(c)).collect();
+                writeln!(out, "{}", cells.join(",")).unwrap();
+            }
+        }
+        Format::Json => {
+            out.push('[');
+            for (i, row) in rows.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                let fields: Vec<_> = header
+                    .iter()
+                    .zip(row)
+                    .map(|(&(name, column), cell)| {
+                        let value = match column {
+                            Column::Text => json_string(cell),
+                            Column::Number => json_value(cell),
+                        };
+                        format!("{}: {value}", json_string(name))
+                    })
+                    .collect();
+                write!(out, "\n  {{{}}}", fields.join(", ")).unwrap();
+            }
+            out.push_str(if rows.is_empty() { "]\n" } else { "\n]\n" });
+        }
+    }
+    out
+}
+
+impl<N, S> Index<NodeIndex> for Scores<N, S> {
+    type Output = S;
+
+    fn index(&self, node: NodeIndex) -> &S {
+        self.get(node).expect("no score for this node")
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Finite numbers go out as JSON numbers. Anything else, like `inf` or an
+/// exact fraction such as `4/15`, goes out as a string.
+fn json_value(s: &str) -> String {
+    match s.parse::<f64>() {
+        Ok(x) if x.is_finite() => s.to_string(),
+        _ => json_string(s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::prelude::*;
+
+    fn example() -> Scores<&'static str> {
+        let mut graph: UnGraph<&str, ()> = Graph::new_undirected();
+        graph.add_node("Alice");
+        graph.add_node("Bob, Jr.");
+        graph.add_node("Carol");
+        Scores::new(&graph, [0.5, 2.0, 1.25])
+    }
+
+    #[test]
+    fn lookup_survives_sorting() {
+        let mut scores = example();
+        scores.sort_descending();
+        let order: Vec<_> = scores.iter().map(|e| e.weight).collect();
+        assert_eq!(order, ["Bob, Jr.", "Carol", "Alice"]);
+        assert_eq!(scores[NodeIndex::new(0)], 0.5);
+        assert_eq!(scores.get_by_weight(&"Carol"), Some(&1.25));
+        assert_eq!(scores.get(NodeIndex::new(3)), None);
+    }
+
+    #[test]
+    fn table() {
+        let expected = "\
+node  name      score
+   0  Alice       0.5
+   1  Bob, Jr.      2
+   2  Carol      1.25
+";
+        assert_eq!(example().render(Format::Table, "score"), expected);
+    }
+
+    #[test]
+    fn csv() {
+        let expected = "node,name,score\n0,Alice,0.5\n1,\"Bob, Jr.\",2\n2,Carol,1.25\n";
+        assert_eq!(example().render(Format::Csv, "score"), expected);
+    }
+
+    #[test]
+    fn json() {
+        let scores = example().map(|x| if x > 1.0 { f64::INFINITY } else { x });
+        let expected = r#"[
+  {"node": 0, "name": "Alice", "score": 0.5},
+  {"node": 1, "name": "Bob, Jr.", "score": "inf"},
+  {"node": 2, "name": "Carol", "score": "inf"}
+]
+"#;
+        assert_eq!(scores.render(Format::Json, "score"), expected);
+    }
+}
@@ -0,0 +1,222 @@
+//! Minimum spanning trees by Kruskal's and Prim's algorithms. On a
+//! disconnected graph both return a minimum spanning forest, one tree per
+//! component.
+
+use std::collections::BinaryHeap;
+use std::fmt::Display;
+
+use petgraph::dot::{Config, Dot};
+use petgraph::graph::{EdgeIndex, EdgeReference, UnGraph};
+use petgraph::unionfind::UnionFind;
+use petgraph::visit::EdgeRef;
+
+use crate::paths::MinScored;
+
+/// The edges of a minimum spanning forest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpanningForest {
+    /// Edges of the original graph, in the order they were added.
+    pub edges: Vec<EdgeIndex>,
+    pub total_weight: f64,
+    /// Number of trees, which is the number of connected components.
+    pub trees: usize,
+    /// Whether each edge of the original graph is in the forest.
+    in_forest: Vec<bool>,
+}
+
+impl SpanningForest {
+    fn new(edge_count: usize, trees: usize) -> Self {
+        SpanningForest { edges: Vec::new(), total_weight: 0.0, trees, in_forest: vec![false; edge_count] }
+    }
+
+    fn add(&mut self, edge: EdgeIndex, weight: f64) {
+        self.edges.push(edge);
+        self.total_weight += weight;
+        self.in_forest[edge.index()] = true;
+    }
+
+    pub fn contains(&self, edge: EdgeIndex) -> bool {
+        self.in_forest.get(edge.index()).copied().unwrap_or(false)
+    }
+
+    /// The forest as a graph of its own. Vertices keep their indices, edges
+    /// don't.
+    pub fn to_graph<N: Clone, E: Clone>(&self, graph: &UnGraph<N, E>) -> UnGraph<N, E> {
+        graph.filter_map(|_, n| Some(n.clone()), |e, w| self.contains(e).then(|| w.clone()))
+    }
+
+    /// The whole graph in DOT, labelling each edge with its weight and drawing
+    /// the forest's edges bold and red.
+    pub fn dot<N: Display, E, F>(&self, graph: &UnGraph<N, E>, edge_weight: F) -> String
+    where
+        F: Fn(EdgeReference<E>) -> f64,
+    {
+        let weights: Vec<f64> = graph.edge_references().map(&edge_weight).collect();
+        // Dot wants displayable edge weights even when it doesn't print them.
+        let graph = graph.map(|_, n| n, |_, _| "");
+        let edge = |_, e: EdgeReference<&str>| {
+            let label = format!("label = \"{}\"", weights[e.id().index()]);
+            if self.contains(e.id()) {
+                format!("{label} color = red penwidth = 3")
+            } else {
+                format!("{label} color = gray")
+            }
+        };
+        let node = |_, _| String::new();
+        let dot = Dot::with_attr_getters(&graph, &[Config::EdgeNoLabel], &edge, &node);
+        format!("{dot}")
+    }
+}
+
+/// Kruskal's algorithm: take the edges from lightest to heaviest, keeping
+/// each one that joins two different trees. Ties go to the lower edge index.
+pub fn kruskal<N, E, F>(graph: &UnGraph<N, E>, edge_weight: F) -> SpanningForest
+where
+    F: Fn(EdgeReference<E>) -> f64,
+{
+    let mut edges: Vec<(f64, EdgeIndex)> = graph.edge_references().map(|e| (edge_weight(e), e.id())).collect();
+    edges.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let mut sets = UnionFind::new(graph.node_count());
+    let mut forest = SpanningForest::new(graph.edge_count(), graph.node_count());
+    for (w, e) in edges {
+        let (u, v) = graph.edge_endpoints(e).unwrap();
+        if sets.union(u.index(), v.index()) {
+            forest.add(e, w);
+            forest.trees -= 1;
+        }
+    }
+    forest
+}
+
+/// Prim's algorithm: grow a tree from the lowest unvisited vertex, always
+/// adding the lightest edge that leaves it, and start a new tree whenever one
+/// runs out of edges. Ties go to the lower edge index.
+pub fn prim<N, E, F>(graph: &UnGraph<N, E>, edge_weight: F) -> SpanningForest
+where
+    F: Fn(EdgeReference<E>) -> f64,
+{
+    let mut visited = vec![false; graph.node_count()];
+    let mut forest = SpanningForest::new(graph.edge_count(), 0);
+    let mut queue = BinaryHeap::new();
+    for root in graph.node_indices() {
+        if visited[root.index()] {
+            continue;
+        }
+        forest.trees += 1;
+        visited[root.index()] = true;
+        queue.extend(graph.edges(root).map(|e| MinScored((edge_weight(e), e.id()), ())));
+        while let Some(MinScored((w, e), ())) = queue.pop() {
+            let (u, v) = graph.edge_endpoints(e).unwrap();
+            let next = if visited[u.index()] { v } else { u };
+            if visited[next.index()] {
+                continue;
+            }
+            visited[next.index()] = true;
+            forest.add(e, w);
+            queue.extend(graph.edges(next).map(|e| MinScored((edge_weight(e), e.id()), ())));
+        }
+    }
+    forest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::prelude::*;
+
+    /// The kingdom with its roads made two-way, so most places are joined by
+    /// two roads of different lengths, and castle - treasure by two of the
+    /// same length.
+    fn kingdom() -> UnGraph<&'static str, f64> {
+        crate::examples::kingdom().into_edge_type()
+    }
+
+    fn edge_names(graph: &UnGraph<&'static str, f64>, forest: &SpanningForest) -> Vec<(&'static str, &'static str, f64)> {
+        let mut names: Vec<_> = forest
+            .edges
+            .iter()
+            .map(|&e| {
+                let (u, v) = graph.edge_endpoints(e).unwrap();
+                let (u, v) = (graph[u].min(graph[v]), graph[u].max(graph[v]));
+                (u, v, graph[e])
+            })
+            .collect();
+        names.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        names
+    }
+
+    #[test]
+    fn kingdom_agrees_with_networkx() {
+        // nx.minimum_spanning_tree(nx.MultiGraph(edges))
+        let graph = kingdom();
+        let kruskal = kruskal(&graph, |e| *e.weight());
+        let prim = prim(&graph, |e| *e.weight());
+        for forest in [&kruskal, &prim] {
+            assert_eq!(forest.total_weight, 420.0);
+            assert_eq!(forest.trees, 1);
+            assert_eq!(forest.edges.len(), graph.node_count() - 1);
+        }
+        let expected = [
+            ("beach", "city", 29.0), ("castle", "city", 36.0), ("castle", "treasure", 76.0),
+            ("cave", "forest", 19.0), ("cave", "inferno", 17.0), ("city", "start", 33.0),
+            ("desert", "forest", 56.0), ("forest", "mountains", 38.0), ("forest", "start", 42.0),
+            ("glacier", "mountains", 25.0), ("sea", "start", 49.0),
+        ];
+        assert_eq!(edge_names(&graph, &kruskal), expected);
+        assert_eq!(edge_names(&graph, &prim), expected);
+        // Of the two equally long roads to the treasure, both take the first.
+        let first = graph.edge_indices().find(|&e| graph[e] == 76.0).unwrap();
+        assert!(kruskal.contains(first) && prim.contains(first));
+    }
+
+    #[test]
+    fn ties_agree_on_weight() {
+        // A 4 x 4 grid where every edge weighs the same has many minimum
+        // spanning trees.
+        let mut graph: UnGraph<(), f64> = Graph::new_undirected();
+        let v: Vec<_> = (0..16).map(|_| graph.add_node(())).collect();
+        for i in 0..16 {
+            if i % 4 < 3 {
+                graph.add_edge(v[i], v[i + 1], 1.0);
+            }
+            if i < 12 {
+                graph.add_edge(v[i], v[i + 4], 1.0);
+            }
+        }
+        let kruskal = kruskal(&graph, |e| *e.weight());
+        let prim = prim(&graph, |e| *e.weight());
+        assert_eq!(kruskal.total_weight, 15.0);
+        assert_eq!(prim.total_weight, 15.0);
+        assert_eq!(kruskal.to_graph(&graph).edge_count(), 15);
+    }
+
+    #[test]
+    fn forest() {
+        // Two triangles and an isolated vertex.
+        let mut graph: UnGraph<&str, f64> = Graph::new_undirected();
+        let v: Vec<_> = (0..7).map(|_| graph.add_node("")).collect();
+        graph.extend_with_edges([
+            (v[0], v[1], 1.0), (v[1], v[2], 2.0), (v[2], v[0], 3.0),
+            (v[3], v[4], 5.0), (v[4], v[5], 4.0), (v[5], v[3], 4.0),
+        ]);
+        for forest in [kruskal(&graph, |e| *e.weight()), prim(&graph, |e| *e.weight())] {
+            assert_eq!(forest.trees, 3);
+            assert_eq!(forest.total_weight, 11.0);
+            let tree = forest.to_graph(&graph);
+            assert_eq!(tree.node_count(), 7);
+            assert_eq!(crate::connectivity::Connectivity::of(&tree).weak_count(), 3);
+        }
+    }
+
+    #[test]
+    fn dot_highlights_the_tree() {
+        let graph = kingdom();
+        let forest = kruskal(&graph, |e| *e.weight());
+        let dot = forest.dot(&graph, |e| *e.weight());
+        assert!(dot.starts_with("graph {"));
+        assert_eq!(dot.matches("color = red").count(), 11);
+        assert_eq!(dot.matches("color = gray").count(), graph.edge_count() - 11);
+        assert!(dot.contains("label = \"19\" color = red"));
+    }
+}
@@ -0,0 +1,277 @@
+//! Eigenvector, Katz and HITS centrality by power iteration. Like `pagerank`,
+//! each one reports how the iteration finished, and the update rules follow
+//! networkx's pure Python implementations.
+
+use petgraph::graph::{EdgeReference, Graph};
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+
+use crate::pagerank::Convergence;
+use crate::scores::Scores;
+
+/// When to stop a power iteration.
+#[derive(Clone, Copy, Debug)]
+pub struct Iteration {
+    /// Stop once the L1 distance between successive iterations is smaller
+    /// than this.
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for Iteration {
+    fn default() -> Self {
+        Iteration { tolerance: 1e-6, max_iterations: 100 }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Katz {
+    /// Attenuation per hop. The iteration only converges when this is below
+    /// 1 / λ, the reciprocal of the adjacency matrix's largest eigenvalue.
+    pub alpha: f64,
+    /// Centrality every vertex gets for free.
+    pub beta: f64,
+    /// Scale the result to unit Euclidean length, as networkx does.
+    pub normalized: bool,
+    pub iteration: Iteration,
+}
+
+impl Default for Katz {
+    fn default() -> Self {
+        Katz { alpha: 0.1, beta: 1.0, normalized: true, iteration: Iteration { max_iterations: 1000, ..Default::default() } }
+    }
+}
+
+/// Hub and authority scores from `hits`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hits<N> {
+    pub hubs: Scores<N>,
+    pub authorities: Scores<N>,
+}
+
+/// Eigenvector centrality, highest first: each vertex scores in proportion to
+/// the sum of its in-neighbors' scores, weighted by `edge_weight`. The result
+/// has unit Euclidean length.
+///
+/// The iteration multiplies by A + I rather than A so that it still converges
+/// on bipartite graphs, where the powers of A alone oscillate. The shift
+/// doesn't change the eigenvector.
+///
+/// The eigenvector is only unique when the graph is strongly connected.
+/// Otherwise the scores pile up in the component with the largest eigenvalue,
+/// or drain away entirely on a DAG; check with `Connectivity::of` first, and
+/// use Katz centrality or PageRank there.
+pub fn eigenvector<N, E, Ty, F>(graph: &Graph<N, E, Ty>, edge_weight: F, options: Iteration) -> (Scores<N>, Convergence)
+where
+    N: Clone,
+    Ty: EdgeType,
+    F: Fn(EdgeReference<E>) -> f64,
+{
+    let n = graph.node_count();
+    let mut x = vec![1.0 / n as f64; n];
+    let convergence = iterate(&mut x, options, |last| {
+        let mut x = last.to_vec();
+        spread(graph, &edge_weight, last, &mut x);
+        normalize(&mut x, euclidean);
+        x
+    });
+    let mut scores = Scores::new(graph, x);
+    scores.sort_descending();
+    (scores, convergence)
+}
+
+/// Katz centrality, highest first: x = alpha Aᵀx + beta, so every walk that
+/// ends at a vertex counts, attenuated by alpha per hop.
+pub fn katz<N, E, Ty, F>(graph: &Graph<N, E, Ty>, edge_weight: F, options: Katz) -> (Scores<N>, Convergence)
+where
+    N: Clone,
+    Ty: EdgeType,
+    F: Fn(EdgeReference<E>) -> f64,
+{
+    let n = graph.node_count();
+    let mut x = vec![0.0; n];
+    let convergence = iterate(&mut x, options.iteration, |last| {
+        let mut x = vec![0.0; n];
+        spread(graph, &edge_weight, last, &mut x);
+        x.iter_mut().for_each(|v| *v = options.alpha * *v + options.beta);
+        x
+    });
+    if options.normalized {
+        normalize(&mut x, euclidean);
+    }
+    let mut scores = Scores::new(graph, x);
+    scores.sort_descending();
+    (scores, convergence)
+}
+
+/// Kleinberg's hubs and authorities, each highest first and summing to one.
+/// A good authority is pointed to by good hubs, and a good hub points to good
+/// authorities. Undirected edges point both ways, which makes hubs and
+/// authorities the same.
+///
+/// Convergence is measured on the hub scores, scaled so the largest is one.
+pub fn hits<N, E, Ty, F>(graph: &Graph<N, E, Ty>, edge_weight: F, options: Iteration) -> (Hits<N>, Convergence)
+where
+    N: Clone,
+    Ty: EdgeType,
+    F: Fn(EdgeReference<E>) -> f64,
+{
+    let n = graph.node_count();
+    let mut hubs = vec![1.0 / n as f64; n];
+    let mut authorities = vec![0.0; n];
+    let convergence = iterate(&mut hubs, options, |last| {
+        authorities = vec![0.0; n];
+        spread(graph, &edge_weight, last, &mut authorities);
+        let mut hubs = vec![0.0; n];
+        for edge in graph.edge_references() {
+            let w = edge_weight(edge);
+            let (u, v) = (edge.source().index(), edge.target().index());
+            hubs[u] += authorities[v] * w;
+            if !graph.is_directed() && u != v {
+                hubs[v] += authorities[u] * w;
+            }
+        }
+        normalize(&mut hubs, max);
+        normalize(&mut authorities, max);
+        hubs
+    });
+    normalize(&mut hubs, sum);
+    normalize(&mut authorities, sum);
+
+    let mut hubs = Scores::new(graph, hubs);
+    let mut authorities = Scores::new(graph, authorities);
+    hubs.sort_descending();
+    authorities.sort_descending();
+    (Hits { hubs, authorities }, convergence)
+}
+
+/// Replace `x` by `step(x)` until successive vectors are within the
+/// tolerance or we run out of iterations.
+fn iterate(x: &mut Vec<f64>, options: Iteration, mut step: impl FnMut(&[f64]) -> Vec<f64>) -> Convergence {
+    let mut convergence = Convergence { iterations: 0, residual: f64::INFINITY, converged: x.is_empty() };
+    while !convergence.converged && convergence.iterations < options.max_iterations {
+        let next = step(x);
+        convergence.iterations += 1;
+        convergence.residual = next.iter().zip(x.iter()).map(|(a, b)| (a - b).abs()).sum();
+        convergence.converged = convergence.residual < options.tolerance;
+        *x = next;
+    }
+    convergence
+}
+
+/// Add each vertex's `last` score, times the edge weight, to its
+/// out-neighbors in `x`.
+fn spread<N, E, Ty, F>(graph: &Graph<N, E, Ty>, edge_weight: F, last: &[f64], x: &mut [f64])
+where
+    Ty: EdgeType,
+    F: Fn(EdgeReference<E>) -> f64,
+{
+    for v in graph.node_indices() {
+        for edge in graph.edges(v) {
+            x[edge.target().index()] += last[v.index()] * edge_weight(edge);
+        }
+    }
+}
+
+fn euclidean(x: &[f64]) -> f64 {
+    x.iter().map(|v| v * v).sum::<f64>().sqrt()
+}
+
+fn max(x: &[f64]) -> f64 {
+    x.iter().copied().fold(0.0, f64::max)
+}
+
+fn sum(x: &[f64]) -> f64 {
+    x.iter().sum()
+}
+
+/// Divide by `norm(x)`, leaving an all-zero vector alone.
+fn normalize(x: &mut [f64], norm: fn(&[f64]) -> f64) {
+    let norm = norm(x);
+    if norm > 0.0 {
+        x.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::{alice_gale, friends};
+
+    fn assert_scores(scores: &Scores<&str>, expected: &[(&str, f64)]) {
+        for (name, x) in expected {
+            let actual = scores.get_by_weight(name).unwrap();
+            assert!((actual - x).abs() < 1e-9, "{name}: {actual} != {x}");
+        }
+        let order: Vec<_> = scores.iter().map(|e| e.score).collect();
+        assert!(order.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    fn tight() -> Iteration {
+        Iteration { tolerance: 1e-13, max_iterations: 10000 }
+    }
+
+    #[test]
+    fn networkx_eigenvector() {
+        // nx.eigenvector_centrality(G) on the friends graph.
+        let graph = friends();
+        let (scores, convergence) = eigenvector(&graph, |_| 1.0, tight());
+        assert!(convergence.converged);
+        assert_scores(&scores, &[
+            ("Kim", 0.4860034121), ("Adin", 0.4817378332), ("David", 0.3466958318),
+            ("Jose", 0.3466958318), ("Frank", 0.2086097318), ("Jasmine", 0.2086097318),
+            ("Ivan", 0.2077705554), ("Sven", 0.2075411091), ("Maria", 0.2075411091),
+            ("Jean", 0.1738058517), ("Felix", 0.1738058517), ("Phil", 0.1206860711),
+        ]);
+        assert_eq!(scores.iter().next().unwrap().weight, "Kim");
+    }
+
+    #[test]
+    fn networkx_katz() {
+        // nx.katz_centrality(G, alpha=0.1), with and without normalized=False
+        let graph = friends();
+        let options = Katz { iteration: tight(), ..Default::default() };
+        let (scores, convergence) = katz(&graph, |_| 1.0, options);
+        assert!(convergence.converged);
+        assert_scores(&scores, &[
+            ("Kim", 0.3860628015), ("Adin", 0.3856345253), ("David", 0.3122017757),
+            ("Ivan", 0.2728119825), ("Frank", 0.2540523196), ("Sven", 0.254009492),
+            ("Jean", 0.2501133403), ("Phil", 0.2227893144),
+        ]);
+        let options = Katz { normalized: false, ..options };
+        let (scores, _) = katz(&graph, |_| 1.0, options);
+        assert_scores(&scores, &[("Kim", 2.0955950353), ("Jose", 1.6946685581), ("Phil", 1.2093270301)]);
+    }
+
+    #[test]
+    fn katz_diverges() {
+        // Adin and Kim have degree 6, so alpha = 0.5 is far past 1 / λ.
+        let graph = friends();
+        let options = Katz { alpha: 0.5, ..Default::default() };
+        let (_, convergence) = katz(&graph, |_| 1.0, options);
+        assert!(!convergence.converged);
+        assert_eq!(convergence.iterations, 1000);
+    }
+
+    #[test]
+    fn networkx_hits() {
+        // nx.hits(D) with the weights. Carol's two heavy edges dominate, so
+        // she is the only hub and her targets the only authorities.
+        let graph = alice_gale();
+        let (Hits { hubs, authorities }, convergence) = hits(&graph, |e| *e.weight(), tight());
+        assert!(convergence.converged);
+        assert_scores(&hubs, &[("Carol", 1.0), ("Alice", 0.0), ("Frank", 0.0), ("Gale", 0.0)]);
+        assert_scores(&authorities, &[
+            ("Eve", 0.5652173913), ("Dan", 0.4347826087), ("Carol", 0.0), ("Gale", 0.0),
+        ]);
+    }
+
+    #[test]
+    fn undirected_hubs_are_authorities() {
+        let graph = friends();
+        let (Hits { hubs, authorities }, _) = hits(&graph, |_| 1.0, tight());
+        for v in graph.node_indices() {
+            assert!((hubs[v] - authorities[v]).abs() < 1e-9);
+        }
+    }
+}
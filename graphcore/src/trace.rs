@@ -0,0 +1,488 @@
+//! Breadth-first search, depth-first search, Dijkstra and A* that record
+//! every step they take, for watching how each one explores a graph.
+//!
+//! A traversal is a loop: pop a vertex from the frontier, visit it unless it
+//! was visited already, and relax each edge to an unvisited neighbor,
+//! pushing the neighbor when that finds it a shorter path. Only the frontier
+//! differs: a queue for BFS, a stack for DFS, and a priority queue ordered by
+//! distance for Dijkstra or by distance plus estimate for A*. Vertices are
+//! never removed from the frontier early, so a pop can find a vertex that is
+//! already visited; that shows up as a `Pop` with no `Visit` after it.
+//!
+//! The events replay as DOT frames, one per step, or as a JSON log.
+
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt::{self, Display, Write};
+
+use petgraph::dot::{Config, Dot};
+use petgraph::graph::{EdgeIndex, EdgeReference, Graph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+
+use crate::paths::MinScored;
+use crate::scores::json_string;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    BreadthFirst,
+    DepthFirst,
+    Dijkstra,
+    AStar,
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Algorithm::BreadthFirst => "BFS",
+            Algorithm::DepthFirst => "DFS",
+            Algorithm::Dijkstra => "Dijkstra",
+            Algorithm::AStar => "A*",
+        })
+    }
+}
+
+/// One step of a traversal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    /// `node` joins the frontier. The priority is the number of hops for BFS
+    /// and DFS, the distance for Dijkstra and the distance plus the estimate
+    /// for A*.
+    Push { node: NodeIndex, priority: f64 },
+    /// `node` leaves the frontier.
+    Pop { node: NodeIndex, priority: f64 },
+    /// `node` is visited at `distance` from the source. For Dijkstra and A*
+    /// that distance is final.
+    Visit { node: NodeIndex, distance: f64 },
+    /// The edge from a visited vertex to an unvisited one was considered,
+    /// giving `distance` to `to`. `improved` says whether that beat the best
+    /// distance to `to` so far.
+    Relax { edge: EdgeIndex, from: NodeIndex, to: NodeIndex, distance: f64, improved: bool },
+}
+
+/// Everything one traversal did, and where it ended up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trace {
+    pub algorithm: Algorithm,
+    pub source: NodeIndex,
+    pub events: Vec<Event>,
+    /// Distance of each visited vertex, by the path the traversal found.
+    pub distance: Vec<Option<f64>>,
+    /// The vertex each one was visited from.
+    pub parent: Vec<Option<NodeIndex>>,
+}
+
+/// Breadth-first search from `source`, counting hops.
+pub fn bfs<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, source: NodeIndex) -> Trace {
+    traverse(graph, source, None, Algorithm::BreadthFirst, |_| 1.0, |_| 0.0)
+}
+
+/// Depth-first search from `source`, counting hops along the tree it builds.
+pub fn dfs<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, source: NodeIndex) -> Trace {
+    traverse(graph, source, None, Algorithm::DepthFirst, |_| 1.0, |_| 0.0)
+}
+
+/// Dijkstra's algorithm from `source`, stopping once `goal` is visited if
+/// there is one. Edge costs must not be negative.
+pub fn dijkstra<N, E, Ty, F>(graph: &Graph<N, E, Ty>, source: NodeIndex, goal: Option<NodeIndex>, edge_cost: F) -> Trace
+where
+    Ty: EdgeType,
+    F: Fn(EdgeReference<E>) -> f64,
+{
+    traverse(graph, source, goal, Algorithm::Dijkstra, edge_cost, |_| 0.0)
+}
+
+/// A* from `source` to `goal`. `estimate` must never overestimate the
+/// distance left to the goal, as for `petgraph::algo::astar`.
+pub fn astar<N, E, Ty, F, H>(graph: &Graph<N, E, Ty>, source: NodeIndex, goal: NodeIndex, edge_cost: F, estimate: H) -> Trace
+where
+    Ty: EdgeType,
+    F: Fn(EdgeReference<E>) -> f64,
+    H: Fn(NodeIndex) -> f64,
+{
+    traverse(graph, source, Some(goal), Algorithm::AStar, edge_cost, estimate)
+}
+
+fn traverse<N, E, Ty, F, H>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeIndex,
+    goal: Option<NodeIndex>,
+    algorithm: Algorithm,
+    edge_cost: F,
+    estimate: H,
+) -> Trace
+where
+    Ty: EdgeType,
+    F: Fn(EdgeReference<E>) -> f64,
+    H: Fn(NodeIndex) -> f64,
+{
+    let n = graph.node_count();
+    let mut trace = Trace { algorithm, source, events: Vec::new(), distance: vec![None; n], parent: vec![None; n] };
+    // Best distance found so far, visited or not.
+    let mut best: Vec<Option<f64>> = vec![None; n];
+    let mut visited = vec![false; n];
+    let mut frontier = Frontier::new(algorithm);
+
+    best[source.index()] = Some(0.0);
+    let priority = estimate(source);
+    frontier.push(Entry { priority, sequence: 0, node: source, distance: 0.0, parent: None });
+    trace.events.push(Event::Push { node: source, priority });
+    let mut sequence = 1;
+
+    while let Some(Entry { priority, node: v, distance, parent, .. }) = frontier.pop() {
+        trace.events.push(Event::Pop { node: v, priority });
+        if visited[v.index()] {
+            continue;
+        }
+        visited[v.index()] = true;
+        trace.distance[v.index()] = Some(distance);
+        trace.parent[v.index()] = parent;
+        trace.events.push(Event::Visit { node: v, distance });
+        if Some(v) == goal {
+            break;
+        }
+        for edge in graph.edges(v) {
+            let w = edge.target();
+            if visited[w.index()] {
+                continue;
+            }
+            let alt = distance + edge_cost(edge);
+            let improved = match algorithm {
+                // BFS keeps the first path it finds, which has the fewest hops.
+                Algorithm::BreadthFirst => best[w.index()].is_none(),
+                // DFS visits w from whoever pushed it last.
+                Algorithm::DepthFirst => true,
+                Algorithm::Dijkstra | Algorithm::AStar => best[w.index()].is_none_or(|d| alt < d),
+            };
+            trace.events.push(Event::Relax { edge: edge.id(), from: v, to: w, distance: alt, improved });
+            if improved {
+                best[w.index()] = Some(alt);
+                let priority = alt + estimate(w);
+                frontier.push(Entry { priority, sequence, node: w, distance: alt, parent: Some(v) });
+                sequence += 1;
+                trace.events.push(Event::Push { node: w, priority });
+            }
+        }
+    }
+    trace
+}
+
+/// A frontier entry. The priority queue pops the lowest priority first and,
+/// among equal priorities, the entry pushed first.
+struct Entry {
+    priority: f64,
+    sequence: usize,
+    node: NodeIndex,
+    distance: f64,
+    parent: Option<NodeIndex>,
+}
+
+enum Frontier {
+    Queue(VecDeque<Entry>),
+    Stack(Vec<Entry>),
+    Heap(BinaryHeap<MinScored<(f64, usize), Entry>>),
+}
+
+impl Frontier {
+    fn new(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::BreadthFirst => Frontier::Queue(VecDeque::new()),
+            Algorithm::DepthFirst => Frontier::Stack(Vec::new()),
+            Algorithm::Dijkstra | Algorithm::AStar => Frontier::Heap(BinaryHeap::new()),
+        }
+    }
+
+    fn push(&mut self, entry: Entry) {
+        match self {
+            Frontier::Queue(queue) => queue.push_back(entry),
+            Frontier::Stack(stack) => stack.push(entry),
+            Frontier::Heap(heap) => heap.push(MinScored((entry.priority, entry.sequence), entry)),
+        }
+    }
+
+    fn pop(&mut self) -> Option<Entry> {
+        match self {
+            Frontier::Queue(queue) => queue.pop_front(),
+            Frontier::Stack(stack) => stack.pop(),
+            Frontier::Heap(heap) => heap.pop().map(|MinScored(_, entry)| entry),
+        }
+    }
+}
+
+/// What the traversal knows after some number of events.
+#[derive(Clone, Debug)]
+struct State {
+    visited: Vec<bool>,
+    /// How many times each vertex is on the frontier.
+    frontier: Vec<usize>,
+    /// Best distance found so far.
+    distance: Vec<Option<f64>>,
+}
+
+impl State {
+    fn new(n: usize) -> Self {
+        State { visited: vec![false; n], frontier: vec![0; n], distance: vec![None; n] }
+    }
+
+    fn apply(&mut self, event: &Event) {
+        match *event {
+            Event::Push { node, .. } => self.frontier[node.index()] += 1,
+            Event::Pop { node, .. } => self.frontier[node.index()] -= 1,
+            Event::Visit { node, distance } => {
+                self.visited[node.index()] = true;
+                self.distance[node.index()] = Some(distance);
+            }
+            Event::Relax { to, distance, improved: true, .. } => self.distance[to.index()] = Some(distance),
+            Event::Relax { .. } => (),
+        }
+    }
+}
+
+impl Trace {
+    /// The vertices from the source to `node` along the traversal's tree, or
+    /// nothing if `node` wasn't visited.
+    pub fn path_to(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        if self.distance[node.index()].is_none() {
+            return Vec::new();
+        }
+        let mut path: Vec<_> = std::iter::successors(Some(node), |v| self.parent[v.index()]).collect();
+        path.reverse();
+        path
+    }
+
+    /// Number of vertices visited.
+    pub fn visited(&self) -> usize {
+        self.distance.iter().filter(|d| d.is_some()).count()
+    }
+
+    /// A DOT picture of the graph after each event. Visited vertices are
+    /// filled gray, vertices on the frontier are outlined blue and the vertex
+    /// or edge the event is about is red. Vertices show their best distance
+    /// so far, edges their cost.
+    pub fn frames<N: Display, E, Ty, F>(&self, graph: &Graph<N, E, Ty>, edge_cost: F) -> Vec<String>
+    where
+        Ty: EdgeType,
+        F: Fn(EdgeReference<E>) -> f64,
+    {
+        let costs: Vec<f64> = graph.edge_references().map(&edge_cost).collect();
+        // Dot wants displayable edge weights even when it doesn't print them.
+        let plain = graph.map(|_, n| n, |_, _| "");
+        let mut state = State::new(graph.node_count());
+        let mut frames = Vec::with_capacity(self.events.len());
+        for (step, event) in self.events.iter().enumerate() {
+            state.apply(event);
+            let node = |_, (v, _): (NodeIndex, _)| {
+                let mut attributes = Vec::new();
+                if let Some(d) = state.distance[v.index()] {
+                    attributes.push(format!("xlabel = \"{d}\""));
+                }
+                if state.visited[v.index()] {
+                    attributes.push("style = filled fillcolor = lightgray".to_string());
+                }
+                let current = match *event {
+                    Event::Push { node, .. } | Event::Pop { node, .. } | Event::Visit { node, .. } => node == v,
+                    Event::Relax { .. } => false,
+                };
+                if current {
+                    attributes.push("color = red penwidth = 2".to_string());
+                } else if state.frontier[v.index()] > 0 {
+                    attributes.push("color = blue penwidth = 2".to_string());
+                }
+                attributes.join(" ")
+            };
+            let edge = |_, e: EdgeReference<&str>| {
+                let label = format!("label = \"{}\"", costs[e.id().index()]);
+                match *event {
+                    Event::Relax { edge, .. } if edge == e.id() => format!("{label} color = red penwidth = 2"),
+                    _ => label,
+                }
+            };
+            let dot = format!("{}", Dot::with_attr_getters(&plain, &[Config::EdgeNoLabel], &edge, &node));
+            // Caption each frame with the step it shows.
+            let (first, rest) = dot.split_once('\n').unwrap();
+            let caption = json_string(&format!("{} step {}: {}", self.algorithm, step + 1, self.describe(graph, event)));
+            frames.push(format!("{first}\n    label = {caption}\n    labelloc = t\n{rest}"));
+        }
+        frames
+    }
+
+    /// The events as a JSON array of objects, naming vertices by their
+    /// weights. Each visit also lists every vertex visited so far.
+    pub fn json<N: Display, E, Ty: EdgeType>(&self, graph: &Graph<N, E, Ty>) -> String {
+        let name = |v: NodeIndex| json_string(&graph[v].to_string());
+        let mut state = State::new(graph.node_count());
+        let mut out = String::from("[");
+        for (step, event) in self.events.iter().enumerate() {
+            state.apply(event);
+            if step > 0 {
+                out.push(',');
+            }
+            write!(out, "\n  {{\"step\": {}, ", step + 1).unwrap();
+            match *event {
+                Event::Push { node, priority } => {
+                    write!(out, "\"event\": \"push\", \"node\": {}, \"priority\": {priority}", name(node))
+                }
+                Event::Pop { node, priority } => {
+                    write!(out, "\"event\": \"pop\", \"node\": {}, \"priority\": {priority}", name(node))
+                }
+                Event::Visit { node, distance } => {
+                    let visited: Vec<_> = graph.node_indices().filter(|v| state.visited[v.index()]).map(name).collect();
+                    write!(
+                        out,
+                        "\"event\": \"visit\", \"node\": {}, \"distance\": {distance}, \"visited\": [{}]",
+                        name(node),
+                        visited.join(", ")
+                    )
+                }
+                Event::Relax { edge, from, to, distance, improved } => write!(
+                    out,
+                    "\"event\": \"relax\", \"edge\": {}, \"from\": {}, \"to\": {}, \"distance\": {distance}, \"improved\": {improved}",
+                    edge.index(),
+                    name(from),
+                    name(to)
+                ),
+            }
+            .unwrap();
+            out.push('}');
+        }
+        out.push_str(if self.events.is_empty() { "]\n" } else { "\n]\n" });
+        out
+    }
+
+    fn describe<N: Display, E, Ty: EdgeType>(&self, graph: &Graph<N, E, Ty>, event: &Event) -> String {
+        match *event {
+            Event::Push { node, priority } => format!("push {} ({priority})", graph[node]),
+            Event::Pop { node, priority } => format!("pop {} ({priority})", graph[node]),
+            Event::Visit { node, distance } => format!("visit {} at {distance}", graph[node]),
+            Event::Relax { from, to, distance, improved, .. } => {
+                let verdict = if improved { "shorter" } else { "no shorter" };
+                format!("relax {} - {}, {distance} is {verdict}", graph[from], graph[to])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::prelude::*;
+
+    /// `paris-brussels-hague.dot`, with the road distances in kilometres
+    /// from its edge labels.
+    fn paris_brussels_hague() -> UnGraph<String, f64> {
+        let graph = crate::dot::read_graph(include_str!("../../paris-brussels-hague.dot")).unwrap();
+        graph.map(|_, node| node.name.clone(), |_, attributes| attributes["label"].parse().unwrap())
+    }
+
+    #[test]
+    fn dijkstra_paris_to_hague() {
+        let graph = paris_brussels_hague();
+        let [paris, brussels, hague] = [0, 1, 2].map(NodeIndex::new);
+        let trace = dijkstra(&graph, paris, Some(hague), |e| *e.weight());
+        assert_eq!(trace.events, [
+            Event::Push { node: paris, priority: 0.0 },
+            Event::Pop { node: paris, priority: 0.0 },
+            Event::Visit { node: paris, distance: 0.0 },
+            Event::Relax { edge: EdgeIndex::new(0), from: paris, to: brussels, distance: 350.0, improved: true },
+            Event::Push { node: brussels, priority: 350.0 },
+            Event::Pop { node: brussels, priority: 350.0 },
+            Event::Visit { node: brussels, distance: 350.0 },
+            Event::Relax { edge: EdgeIndex::new(1), from: brussels, to: hague, distance: 530.0, improved: true },
+            Event::Push { node: hague, priority: 530.0 },
+            Event::Pop { node: hague, priority: 530.0 },
+            Event::Visit { node: hague, distance: 530.0 },
+        ]);
+        assert_eq!(trace.path_to(hague), [paris, brussels, hague]);
+
+        let frames = trace.frames(&graph, |e| *e.weight());
+        assert_eq!(frames.len(), trace.events.len());
+        assert!(frames[0].starts_with("graph {\n    label = \"Dijkstra step 1: push Paris (0)\""));
+        assert!(frames[4].contains("label = \"Brussels\" xlabel = \"350\" color = red"));
+        assert!(frames[7].contains("1 -- 2 [ label = \"180\" color = red penwidth = 2]"));
+        assert_eq!(frames[10].matches("fillcolor = lightgray").count(), 3);
+
+        let json = trace.json(&graph);
+        assert!(json.starts_with("[\n  {\"step\": 1, \"event\": \"push\", \"node\": \"Paris\", \"priority\": 0},"));
+        assert!(json.contains(
+            "{\"step\": 8, \"event\": \"relax\", \"edge\": 1, \"from\": \"Brussels\", \"to\": \"Hague\", \"distance\": 530, \"improved\": true}"
+        ));
+        assert!(json.ends_with(
+            "{\"step\": 11, \"event\": \"visit\", \"node\": \"Hague\", \"distance\": 530, \"visited\": [\"Paris\", \"Brussels\", \"Hague\"]}\n]\n"
+        ));
+    }
+
+    /// The square 0 - 1 - 3 - 2 - 0 with costs 1, 5, 1 and 4, so the cheap
+    /// way to 3 is through 2, plus 3 - 4 (cost 1) and 1 - 5 (cost 9).
+    fn diamond() -> UnGraph<usize, f64> {
+        let mut graph = Graph::new_undirected();
+        let v: Vec<_> = (0..6).map(|i| graph.add_node(i)).collect();
+        graph.extend_with_edges([
+            (v[0], v[1], 1.0), (v[0], v[2], 4.0), (v[1], v[3], 5.0),
+            (v[2], v[3], 1.0), (v[3], v[4], 1.0), (v[1], v[5], 9.0),
+        ]);
+        graph
+    }
+
+    fn visits(trace: &Trace) -> Vec<usize> {
+        trace
+            .events
+            .iter()
+            .filter_map(|e| match e {
+                Event::Visit { node, .. } => Some(node.index()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bfs_and_dfs() {
+        let graph = diamond();
+        let bfs = bfs(&graph, NodeIndex::new(0));
+        assert_eq!(bfs.visited(), 6);
+        assert_eq!(bfs.distance, [0.0, 1.0, 1.0, 2.0, 3.0, 2.0].map(Some));
+        // Hops, not costs, so 3 is reached through whichever of 1 and 2 came
+        // out of the queue first.
+        assert_eq!(bfs.path_to(NodeIndex::new(4)).len(), 4);
+
+        let dfs = dfs(&graph, NodeIndex::new(0));
+        assert_eq!(dfs.visited(), 6);
+        // DFS goes deep before wide: every vertex is visited from the one
+        // visited most recently among its neighbors.
+        let order = visits(&dfs);
+        assert_eq!(order[0], 0);
+        for (i, &v) in order.iter().enumerate().skip(1) {
+            let parent = dfs.parent[v].unwrap().index();
+            let latest = order[..i].iter().rev().find(|&&u| graph.find_edge(NodeIndex::new(u), NodeIndex::new(v)).is_some());
+            assert_eq!(Some(&parent), latest);
+        }
+        // 1 and 2 each led to 3 along a different path, and one of those
+        // pushes was popped after 3 was visited already.
+        let pops = dfs.events.iter().filter(|e| matches!(e, Event::Pop { .. })).count();
+        assert!(pops > dfs.visited());
+    }
+
+    #[test]
+    fn astar_visits_less_than_dijkstra() {
+        // Straight-line estimates to 4 that never overestimate.
+        let graph = diamond();
+        let estimate = [5.0, 6.0, 2.0, 1.0, 0.0, 14.0];
+        let (source, goal) = (NodeIndex::new(0), NodeIndex::new(4));
+        let dijkstra = dijkstra(&graph, source, Some(goal), |e| *e.weight());
+        let astar = astar(&graph, source, goal, |e| *e.weight(), |v| estimate[v.index()]);
+        for trace in [&dijkstra, &astar] {
+            assert_eq!(trace.distance[goal.index()], Some(6.0));
+            assert_eq!(trace.path_to(goal), [0, 2, 3, 4].map(NodeIndex::new));
+        }
+        assert_eq!(visits(&dijkstra), [0, 1, 2, 3, 4]);
+        assert_eq!(visits(&astar), [0, 2, 3, 4]);
+        // Dijkstra found 3 through 1 first, then a shorter path through 2.
+        assert!(dijkstra.events.contains(&Event::Relax {
+            edge: EdgeIndex::new(3),
+            from: NodeIndex::new(2),
+            to: NodeIndex::new(3),
+            distance: 5.0,
+            improved: true,
+        }));
+        let json = astar.json(&graph);
+        assert_eq!(json.matches("\"event\": \"visit\"").count(), 4);
+    }
+}
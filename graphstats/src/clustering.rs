@@ -0,0 +1,279 @@
+//! How cliquish a network is: triangles, clustering coefficients,
+//! transitivity and the k-core decomposition. Like networkx, these ignore
+//! self-loops and count parallel edges once.
+
+use graphcore::scores::Scores;
+use petgraph::graph::{NodeIndex, UnGraph};
+
+/// The distinct neighbors of every vertex, without the vertex itself.
+fn neighbors<N, E>(graph: &UnGraph<N, E>) -> Vec<Vec<usize>> {
+    graph
+        .node_indices()
+        .map(|v| {
+            let mut list: Vec<usize> = graph.neighbors(v).filter(|&w| w != v).map(|w| w.index()).collect();
+            list.sort_unstable();
+            list.dedup();
+            list
+        })
+        .collect()
+}
+
+/// Triangles through every vertex by the forward algorithm: order the
+/// vertices by degree and point every edge from the earlier vertex to the
+/// later, so that each triangle is found exactly once, from its earliest
+/// vertex, and nobody has more than O(√m) later neighbors.
+fn triangle_counts(neighbors: &[Vec<usize>]) -> Vec<usize> {
+    let n = neighbors.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&v| (neighbors[v].len(), v));
+    let mut rank = vec![0; n];
+    for (i, &v) in order.iter().enumerate() {
+        rank[v] = i;
+    }
+    let later: Vec<Vec<usize>> =
+        neighbors.iter().enumerate().map(|(v, list)| list.iter().copied().filter(|&w| rank[w] > rank[v]).collect()).collect();
+
+    let mut count = vec![0; n];
+    let mut marked = vec![false; n];
+    for u in 0..n {
+        for &v in &later[u] {
+            marked[v] = true;
+        }
+        for &v in &later[u] {
+            for &w in &later[v] {
+                if marked[w] {
+                    count[u] += 1;
+                    count[v] += 1;
+                    count[w] += 1;
+                }
+            }
+        }
+        for &v in &later[u] {
+            marked[v] = false;
+        }
+    }
+    count
+}
+
+/// The number of triangles through every vertex.
+pub fn triangles<N: Clone, E>(graph: &UnGraph<N, E>) -> Scores<N, usize> {
+    Scores::new(graph, triangle_counts(&neighbors(graph)))
+}
+
+/// The local clustering coefficient of every vertex: the fraction of pairs
+/// of its neighbors that are neighbors themselves, or 0 with fewer than two
+/// neighbors.
+pub fn clustering<N: Clone, E>(graph: &UnGraph<N, E>) -> Scores<N> {
+    Scores::new(graph, local_clustering(graph))
+}
+
+fn local_clustering<N, E>(graph: &UnGraph<N, E>) -> Vec<f64> {
+    let neighbors = neighbors(graph);
+    let triangles = triangle_counts(&neighbors);
+    neighbors
+        .iter()
+        .zip(triangles)
+        .map(|(list, t)| {
+            let d = list.len();
+            if d < 2 { 0.0 } else { 2.0 * t as f64 / (d * (d - 1)) as f64 }
+        })
+        .collect()
+}
+
+/// Watts and Strogatz's global clustering coefficient, the mean of the
+/// local ones.
+pub fn average_clustering<N, E>(graph: &UnGraph<N, E>) -> f64 {
+    let local = local_clustering(graph);
+    local.iter().sum::<f64>() / local.len() as f64
+}
+
+/// Three times the number of triangles over the number of paths of length
+/// two, or 0 without any such paths.
+pub fn transitivity<N, E>(graph: &UnGraph<N, E>) -> f64 {
+    let neighbors = neighbors(graph);
+    let closed: usize = triangle_counts(&neighbors).iter().sum();
+    let triads: usize = neighbors.iter().map(|list| list.len() * list.len().saturating_sub(1) / 2).sum();
+    if triads == 0 { 0.0 } else { closed as f64 / triads as f64 }
+}
+
+/// The k-core decomposition. The k-core is the largest subgraph in which
+/// every vertex has degree at least k.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cores {
+    /// The largest k with the vertex in the k-core, indexed by
+    /// `NodeIndex::index()`.
+    pub core: Vec<usize>,
+    /// The largest k with a nonempty k-core.
+    pub degeneracy: usize,
+    /// A degeneracy ordering: every vertex has at most `degeneracy`
+    /// neighbors after it.
+    pub order: Vec<NodeIndex>,
+}
+
+impl Cores {
+    /// The vertices of the k-core in index order.
+    pub fn k_core(&self, k: usize) -> Vec<NodeIndex> {
+        (0..self.core.len()).filter(|&v| self.core[v] >= k).map(NodeIndex::new).collect()
+    }
+
+    /// The core number of every vertex, labelled with the vertex weights.
+    pub fn scores<N: Clone, E>(&self, graph: &UnGraph<N, E>) -> Scores<N, usize> {
+        Scores::new(graph, self.core.iter().copied())
+    }
+}
+
+/// Batagelj and Zaversnik's O(m) algorithm: keep the vertices in buckets by
+/// their remaining degree and repeatedly remove one of least degree.
+pub fn cores<N, E>(graph: &UnGraph<N, E>) -> Cores {
+    let neighbors = neighbors(graph);
+    let n = neighbors.len();
+    let mut degree: Vec<usize> = neighbors.iter().map(Vec::len).collect();
+    let max_degree = degree.iter().copied().max().unwrap_or(0);
+
+    // The vertices sorted by degree, where each degree starts and where
+    // each vertex is.
+    let mut start = vec![0; max_degree + 1];
+    for &d in &degree {
+        start[d] += 1;
+    }
+    let mut next = 0;
+    for s in &mut start {
+        (*s, next) = (next, next + *s);
+    }
+    let mut sorted = vec![0; n];
+    let mut position = vec![0; n];
+    {
+        let mut fill = start.clone();
+        for v in 0..n {
+            position[v] = fill[degree[v]];
+            sorted[position[v]] = v;
+            fill[degree[v]] += 1;
+        }
+    }
+
+    for i in 0..n {
+        let v = sorted[i];
+        for &w in &neighbors[v] {
+            if degree[w] > degree[v] {
+                // Swap w to the front of its bucket, then shrink the bucket
+                // past it so that it joins the one below.
+                let d = degree[w];
+                let first = sorted[start[d]];
+                if first != w {
+                    sorted.swap(position[w], start[d]);
+                    position.swap(w, first);
+                }
+                start[d] += 1;
+                degree[w] -= 1;
+            }
+        }
+    }
+
+    Cores {
+        degeneracy: degree.iter().copied().max().unwrap_or(0),
+        order: sorted.into_iter().map(NodeIndex::new).collect(),
+        core: degree,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphcore::examples::friends;
+
+    fn by_name<S: Copy>(scores: &Scores<&str, S>, name: &str) -> S {
+        *scores.get_by_weight(&name).unwrap()
+    }
+
+    #[test]
+    fn networkx_triangles() {
+        // nx.triangles(G)
+        let graph = friends();
+        let triangles = triangles(&graph);
+        let expected = [
+            ("Frank", 1), ("Kim", 4), ("David", 3), ("Adin", 4), ("Maria", 1), ("Jose", 3),
+            ("Jasmine", 1), ("Ivan", 0), ("Felix", 0), ("Sven", 1), ("Jean", 0), ("Phil", 0),
+        ];
+        for (name, t) in expected {
+            assert_eq!(by_name(&triangles, name), t, "{name}");
+        }
+        assert_eq!(triangles.iter().map(|e| e.score).sum::<usize>(), 3 * 6);
+    }
+
+    #[test]
+    fn networkx_clustering() {
+        // nx.clustering(G), nx.average_clustering(G) and nx.transitivity(G)
+        let graph = friends();
+        let clustering = clustering(&graph);
+        let expected = [
+            ("Frank", 1.0), ("Kim", 4.0 / 21.0), ("David", 0.5), ("Adin", 4.0 / 21.0), ("Maria", 1.0),
+            ("Jose", 0.5), ("Jasmine", 1.0), ("Ivan", 0.0), ("Felix", 0.0), ("Sven", 1.0), ("Jean", 0.0),
+            ("Phil", 0.0),
+        ];
+        for (name, c) in expected {
+            let actual = by_name(&clustering, name);
+            assert!((actual - c).abs() < 1e-12, "{name}: {actual} != {c}");
+        }
+        assert!((average_clustering(&graph) - 0.44841269841269843).abs() < 1e-12);
+        assert!((transitivity(&graph) - 2.0 / 7.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ignores_loops_and_parallel_edges() {
+        let mut graph = friends();
+        let find = |graph: &UnGraph<&str, ()>, name| graph.node_indices().find(|&v| graph[v] == name).unwrap();
+        let (kim, jose) = (find(&graph, "Kim"), find(&graph, "Jose"));
+        graph.add_edge(kim, jose, ());
+        graph.add_edge(kim, kim, ());
+        assert_eq!(by_name(&triangles(&graph), "Kim"), 4);
+        assert!((transitivity(&graph) - 2.0 / 7.0).abs() < 1e-12);
+        assert_eq!(cores(&graph), cores(&friends()));
+    }
+
+    #[test]
+    fn networkx_core_number() {
+        // nx.core_number(G): Phil hangs off Adin and everyone else is in
+        // the 2-core.
+        let graph = friends();
+        let cores = cores(&graph);
+        assert_eq!(cores.degeneracy, 2);
+        let scores = cores.scores(&graph);
+        for v in graph.node_indices() {
+            let expected = if graph[v] == "Phil" { 1 } else { 2 };
+            assert_eq!(scores[v], expected, "{}", graph[v]);
+        }
+        assert_eq!(cores.k_core(2).len(), 11);
+        assert_degeneracy_order(&graph, &cores);
+    }
+
+    #[test]
+    fn clique_with_a_tail() {
+        // G = nx.lollipop_graph(5, 3) and nx.core_number(G): the K5 is
+        // the 4-core.
+        let mut graph = UnGraph::<&str, ()>::default();
+        let v: Vec<_> = (0..8).map(|_| graph.add_node("")).collect();
+        for i in 0..5 {
+            for j in i + 1..5 {
+                graph.add_edge(v[i], v[j], ());
+            }
+        }
+        graph.extend_with_edges([(v[4], v[5]), (v[5], v[6]), (v[6], v[7])]);
+        let cores = cores(&graph);
+        assert_eq!(cores.core, [4, 4, 4, 4, 4, 1, 1, 1]);
+        assert_eq!(cores.degeneracy, 4);
+        assert_eq!(cores.k_core(4), &v[..5]);
+        assert_degeneracy_order(&graph, &cores);
+        // nx.average_clustering(G) and nx.transitivity(G)
+        assert!((average_clustering(&graph) - 0.575).abs() < 1e-12);
+        assert!((transitivity(&graph) - 30.0 / 36.0).abs() < 1e-12);
+    }
+
+    fn assert_degeneracy_order(graph: &UnGraph<&str, ()>, cores: &Cores) {
+        let mut seen = vec![false; graph.node_count()];
+        for &v in &cores.order {
+            seen[v.index()] = true;
+            let later = graph.neighbors(v).filter(|w| !seen[w.index()]).count();
+            assert!(later <= cores.degeneracy);
+        }
+    }
+}
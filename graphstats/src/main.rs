@@ -1,50 +1,236 @@
-use petgraph::graph::Graph;
-use petgraph::algo::page_rank;
-use petgraph::Undirected;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use graphcore::community::{self, girvan_newman, louvain};
+use graphcore::connectivity::{cuts, Connectivity, Measure};
+use graphcore::degree::{degrees, fit_power_law, Alternative, Degree, Distribution};
+use graphcore::dot::{parse, read_digraph, read_graph, Attributes, Node};
+use graphcore::examples::{alice_gale, friends};
+use graphcore::generators::barabasi_albert;
+use graphcore::pagerank::{pagerank, PageRank};
+use graphcore::paths::path_summary;
+use graphcore::scores::Format;
+use graphcore::spectral::{eigenvector, hits, katz, Hits, Iteration, Katz};
+use graphcore::trace::dijkstra;
+use petgraph::graph::{EdgeReference, Graph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+
+mod clustering;
+
+use clustering::{average_clustering, clustering, cores, transitivity, triangles};
 
 // https://depth-first.com/articles/2020/02/03/graphs-in-rust-an-introduction-to-petgraph/
 
+const USAGE: &str = "Usage: graphstats [table|csv|json] [communities.dot]
+       graphstats power-law [table|csv|json]
+       graphstats trace graph.dot FROM TO DIR";
+
 fn main() {
-    let mut g: Graph<&str, (), Undirected> = Graph::new_undirected();
-    let frank = g.add_node("Frank");
-    let kim = g.add_node("Kim");
-    let david = g.add_node("David");
-    let adin = g.add_node("Adin");
-    let maria = g.add_node("Maria");
-    let jose = g.add_node("Jose");
-    let jasmine = g.add_node("Jasmine");
-    let ivan = g.add_node("Ivan");
-    let felix = g.add_node("Felix");
-    let sven = g.add_node("Sven");
-    let jean = g.add_node("Jean");
-    let phil = g.add_node("Phil");
-
-    g.extend_with_edges(&[
-        (frank, kim),
-        (frank, david),
-        (david, sven),
-        (david, adin),
-        (adin, maria),
-        (adin, jose),
-        (adin, kim),
-        (maria, jose),
-        (jose, kim),
-        (jasmine, jose),
-        (jasmine, kim),
-        (ivan, adin),
-        (jean, ivan),
-        (felix, ivan),
-        (kim, felix),
-        (kim, david),
-        (kim, jean),
-        (sven, adin),
-        (phil, adin),
-    ]);
-
-    //let g = g;
+    match env::args().nth(1).as_deref() {
+        Some("power-law") => return power_law(Format::from_arg(env::args().nth(2).as_deref(), USAGE)),
+        Some("trace") => return trace(&env::args().skip(2).collect::<Vec<_>>()),
+        _ => {}
+    }
+    let format = Format::from_arg(env::args().nth(1).as_deref(), USAGE);
+
+    let g = friends();
+    let connectivity = Connectivity::of(&g);
+
+    if format == Format::Table {
+        let cuts = cuts(&g);
+        let names = |v: &mut dyn Iterator<Item = NodeIndex>| v.map(|v| g[v]).collect::<Vec<_>>().join(", ");
+        println!("{} connected component(s)", connectivity.weak_count());
+        println!("Articulation points: {}", names(&mut cuts.articulation_points.iter().copied()));
+        let bridges = cuts.bridges.iter().map(|&e| {
+            let (u, v) = g.edge_endpoints(e).unwrap();
+            format!("{} - {}", g[u], g[v])
+        });
+        println!("Bridges: {}", bridges.collect::<Vec<_>>().join(", "));
+        let paths = path_summary(&g, |_| 1.0);
+        println!(
+            "Diameter {}, radius {}, average path length {:.4}",
+            paths.diameter(),
+            paths.radius(),
+            paths.average_reachable_path_length
+        );
+        println!();
+    }
+
     //println!("{}", Dot::new(&g));
 
-    let mut pr = page_rank(&g, 0.75_f32, 10);
-    pr.sort_by(|a,b| a.total_cmp(b));
-    println!("{:?}", pr);
+    // petgraph's page_rank(&g, 0.75, 10) runs a fixed number of iterations.
+    let options = PageRank { damping: 0.75, ..Default::default() };
+    connectivity.warn(Measure::PageRank);
+    let (pr, convergence) = pagerank(&g, |_| 1.0, &options);
+    print!("{}", pr.render(format, "pagerank"));
+    if format == Format::Table {
+        println!("{convergence:?}");
+    }
+
+    connectivity.warn(Measure::Eigenvector);
+    let (ev, convergence) = eigenvector(&g, |_| 1.0, Iteration::default());
+    println!();
+    print!("{}", ev.render(format, "eigenvector"));
+    if format == Format::Table {
+        println!("{convergence:?}");
+    }
+
+    connectivity.warn(Measure::Katz);
+    let (kz, convergence) = katz(&g, |_| 1.0, Katz::default());
+    println!();
+    print!("{}", kz.render(format, "katz"));
+    if format == Format::Table {
+        println!("{convergence:?}");
+    }
+
+    let triangles = triangles(&g);
+    if format == Format::Table {
+        println!();
+        println!(
+            "{} triangles, transitivity {:.4}, average clustering {:.4}",
+            triangles.iter().map(|e| e.score).sum::<usize>() / 3,
+            transitivity(&g),
+            average_clustering(&g)
+        );
+    }
+    println!();
+    print!("{}", triangles.render(format, "triangles"));
+    println!();
+    print!("{}", clustering(&g).render(format, "clustering"));
+
+    let cores = cores(&g);
+    if format == Format::Table {
+        println!();
+        let core: Vec<_> = cores.k_core(cores.degeneracy).into_iter().map(|v| g[v]).collect();
+        println!("Degeneracy {}, the {0}-core: {}", cores.degeneracy, core.join(", "));
+    }
+    println!();
+    print!("{}", cores.scores(&g).render(format, "core"));
+
+    // Hubs and authorities only differ on a directed graph.
+    let directed = alice_gale();
+    Connectivity::of(&directed).warn(Measure::Hits);
+    let (Hits { hubs, authorities }, convergence) = hits(&directed, |e| *e.weight(), Iteration::default());
+    println!();
+    print!("{}", hubs.render(format, "hub"));
+    println!();
+    print!("{}", authorities.render(format, "authority"));
+    if format == Format::Table {
+        println!("{convergence:?}");
+    }
+
+    // Split the friends into cliques by cutting the busiest edges.
+    let split = girvan_newman(&g);
+    let best = &split.levels[split.best];
+    if format == Format::Table {
+        let (u, v) = g.edge_endpoints(split.removed[0]).unwrap();
+        println!();
+        println!("First cut: {} - {}", g[u], g[v]);
+        for (i, level) in split.levels.iter().enumerate() {
+            let marker = if i == split.best { " <- best" } else { "" };
+            println!("{} communities, modularity {:.4}{marker}", level.len(), level.modularity);
+        }
+    }
+    println!();
+    print!("{}", best.scores(&g).render(format, "community"));
+
+    // Louvain usually does better, and much faster.
+    let communities = louvain(&g, |_| 1.0, 1.0);
+    if format == Format::Table {
+        println!();
+        println!("Louvain: {} communities, modularity {:.4}", communities.len(), communities.modularity);
+    }
+    println!();
+    print!("{}", communities.scores(&g).render(format, "louvain"));
+
+    if let Some(path) = env::args().nth(2) {
+        fs::write(&path, community::dot(&g, &communities.community)).unwrap_or_else(|e| panic!("{path}: {e}"));
+    }
+}
+
+/// `graphstats power-law`: friends is too small for a degree distribution to
+/// mean much, so fit a power law to a preferential attachment graph instead.
+fn power_law(format: Format) {
+    let ba = barabasi_albert(2000, 3, 42);
+    let values = degrees(&ba, Degree::Total);
+    let distribution = Distribution::new(&values);
+    if format == Format::Table {
+        println!("Barabasi-Albert graph, 2000 vertices, mean degree {:.2}", distribution.mean());
+        if let Some(fit) = fit_power_law(&values) {
+            println!(
+                "Power law: alpha {:.3}, x_min {}, {} in the tail, KS distance {:.4}, p-value {:.2}",
+                fit.alpha,
+                fit.x_min,
+                fit.tail,
+                fit.ks,
+                fit.p_value(&values, 100, 1)
+            );
+            for alternative in [Alternative::Exponential, Alternative::LogNormal] {
+                let comparison = fit.compare(&values, alternative);
+                println!(
+                    "  vs {}: log likelihood ratio {:.3}, p-value {:.3}",
+                    comparison.fit, comparison.log_likelihood_ratio, comparison.p_value
+                );
+            }
+        }
+        println!();
+    }
+    print!("{}", distribution.render_ccdf(format));
+}
+
+/// `graphstats trace`: Dijkstra from FROM to TO in a DOT file, with each
+/// edge's `label` as its cost (1 without one). Writes a DOT frame per step to
+/// DIR as `step-001.dot` and so on, and the events to `DIR/trace.json`.
+fn trace(args: &[String]) {
+    let [path, from, to, dir] = args else {
+        eprintln!("{USAGE}");
+        process::exit(2);
+    };
+    let fail = |e: &dyn Display| -> ! {
+        eprintln!("{e}");
+        process::exit(1);
+    };
+    let source = fs::read_to_string(path).unwrap_or_else(|e| fail(&format!("{path}: {e}")));
+    let dot = parse(&source).unwrap_or_else(|e| fail(&format!("{path}:{e}")));
+    let (frames, json) = if dot.directed {
+        let graph = read_digraph(&source).unwrap_or_else(|e| fail(&format!("{path}:{e}")));
+        trace_between(&graph, from, to).unwrap_or_else(|e| fail(&format!("{path}: {e}")))
+    } else {
+        let graph = read_graph(&source).unwrap_or_else(|e| fail(&format!("{path}:{e}")));
+        trace_between(&graph, from, to).unwrap_or_else(|e| fail(&format!("{path}: {e}")))
+    };
+    let dir = Path::new(dir);
+    fs::create_dir_all(dir).unwrap_or_else(|e| fail(&format!("{}: {e}", dir.display())));
+    let width = frames.len().to_string().len().max(3);
+    for (i, frame) in frames.iter().enumerate() {
+        let file = dir.join(format!("step-{:0width$}.dot", i + 1));
+        fs::write(&file, frame).unwrap_or_else(|e| fail(&format!("{}: {e}", file.display())));
+    }
+    let file = dir.join("trace.json");
+    fs::write(&file, json).unwrap_or_else(|e| fail(&format!("{}: {e}", file.display())));
+}
+
+/// The frames and JSON log of Dijkstra between the vertices named `from`
+/// and `to`.
+fn trace_between<Ty: EdgeType>(
+    graph: &Graph<Node, Attributes, Ty>,
+    from: &str,
+    to: &str,
+) -> Result<(Vec<String>, String), String> {
+    let find = |name: &str| graph.node_indices().find(|&v| graph[v].name == name).ok_or(format!("no vertex {name:?}"));
+    let (from, to) = (find(from)?, find(to)?);
+    let mut costs = Vec::with_capacity(graph.edge_count());
+    for e in graph.edge_references() {
+        costs.push(match e.weight().get("label") {
+            Some(label) => label.parse::<f64>().map_err(|_| format!("edge label {label:?} is not a number"))?,
+            None => 1.0,
+        });
+    }
+    let cost = |e: EdgeReference<Attributes>| costs[e.id().index()];
+    let trace = dijkstra(graph, from, Some(to), cost);
+    Ok((trace.frames(graph, cost), trace.json(graph)))
 }
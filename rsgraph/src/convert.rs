@@ -0,0 +1,35 @@
+//! graphrs graphs as petgraph graphs, so that the algorithms in `graphcore`
+//! run on them too.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+use petgraph::graph::{Graph, NodeIndex};
+use petgraph::EdgeType;
+
+/// A copy of `graph` with the node names as vertex weights and the graphrs
+/// edge weights as edge weights. Vertices are added in name order and edges
+/// in order of their ends, so the indices don't depend on graphrs's hashing.
+/// `Ty` has to match `graph.specs.directed`.
+pub fn to_petgraph<T, A, Ty>(graph: &graphrs::Graph<T, A>) -> Graph<String, f64, Ty>
+where
+    T: Hash + Eq + Clone + Ord + Debug + Display + Send + Sync,
+    A: Clone + Debug + Send + Sync,
+    Ty: EdgeType,
+{
+    assert_eq!(Ty::is_directed(), graph.specs.directed, "the edge type doesn't match the graph");
+    let mut names: Vec<String> = graph.get_all_nodes().iter().map(|n| n.name.to_string()).collect();
+    names.sort();
+    let mut result = Graph::with_capacity(names.len(), 0);
+    let index: HashMap<String, NodeIndex> = names.into_iter().map(|n| (n.clone(), result.add_node(n))).collect();
+
+    let mut edges: Vec<(NodeIndex, NodeIndex, f64)> = graph
+        .get_all_edges()
+        .iter()
+        .map(|e| (index[&e.u.to_string()], index[&e.v.to_string()], e.weight))
+        .collect();
+    edges.sort_by_key(|&(u, v, _)| (u, v));
+    result.extend_with_edges(edges);
+    result
+}
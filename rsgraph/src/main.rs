@@ -1,11 +1,35 @@
-use graphrs::algorithms::centrality::betweenness;
-use graphrs::algorithms::centrality::closeness;
+use std::env;
+use std::fmt::Display;
+use std::path::Path;
+use std::process;
+
+use graphcore::community::girvan_newman;
 use graphrs::{Edge, Graph, GraphSpecs, Node};
-use polars::prelude::*;
+use petgraph::graph::UnGraph;
+
+mod convert;
+mod report;
+
+use convert::to_petgraph;
+use report::Report;
+
+const USAGE: &str = "Usage: rsgraph [column] [report.csv|report.parquet]";
 
 fn main() {
+    let mut args = env::args().skip(1);
+    let column = args.next().unwrap_or_else(|| "Name".to_string());
+    let output = args.next();
+
     betweenness_example();
 
+    let mut df = report::sort(kingdom(), &column, column != "Name").unwrap_or_else(usage);
+    println!("{}", df);
+    if let Some(path) = output {
+        report::export(&mut df, Path::new(&path)).unwrap_or_else(usage);
+    }
+
+    friends();
+
     //let closeness_statistic = closeness::closeness_centrality(&graph, false, true);
 
     // https://neo4j.com/docs/graph-data-science/current/algorithms/betweenness-centrality/
@@ -15,6 +39,13 @@ fn main() {
     // http://www.orgnet.com/MappingTerroristNetworks.pdf
 }
 
+/// Report a bad column or output file the way `Format::from_arg` reports a
+/// bad format.
+fn usage(e: impl Display) -> ! {
+    eprintln!("{e}\n{USAGE}");
+    process::exit(2)
+}
+
 /**
 
 Try to recreate the 
@@ -99,30 +130,22 @@ fn betweenness_example() {
     ];
 
     let g = Graph::<&str, ()>::new_from_nodes_and_edges(v, e, GraphSpecs::directed()).unwrap();
-    let betweenness_statistic = betweenness::betweenness_centrality(&g, true, false).unwrap();
-    let closeness_statistic = closeness::closeness_centrality(&g, false, false).unwrap();
-    
-    let df1: DataFrame = df!(
-        "Name" => betweenness_statistic.clone().into_keys().collect::<Vec<&str>>(),
-        "Betweenness" => betweenness_statistic.clone().into_values().collect::<Vec<f64>>(),
-    ).unwrap();
-
-    let df2: DataFrame = df!(
-        "Name" => closeness_statistic.clone().into_keys().collect::<Vec<&str>>(),
-        "Closeness" => closeness_statistic.clone().into_values().collect::<Vec<f64>>(),
-    ).unwrap();
-
-    //let mut df = df1.inner_join(&df2, [col("Name")], [col("Name")]).unwrap();
-
-    for mut df in vec![df1, df2] {
-        df.sort_in_place(["Name"], Default::default()).unwrap();
-        println!("{}", df);
-    }
+    // GDS weighs the paths for betweenness but not for closeness, and leaves
+    // out the Wasserman-Faust correction.
+    let df = Report::new(&g)
+        .weighted(true)
+        .weighted_closeness(false)
+        .wf_improved(false)
+        .normalized(false)
+        .build()
+        .unwrap();
+    println!("{}", df);
 }
 
  
-#[allow(dead_code)]
-fn kingdom() {
+/// Every centrality measure for the kingdom map in `kingdom.dot`, with the
+/// travel times as distances.
+fn kingdom() -> polars::prelude::DataFrame {
     let mut graph: Graph<&str, i32> = Graph::new(GraphSpecs::directed_create_missing());
     _ = graph
         .add_edges(vec![
@@ -155,18 +178,10 @@ fn kingdom() {
         ])
         .unwrap();
 
-    let betweenness_statistic = betweenness::betweenness_centrality(&graph, false, true).unwrap();
-    let closeness_statistic = closeness::closeness_centrality(&graph, false, true).unwrap();
-    let df: DataFrame = df!(
-        "Name" => betweenness_statistic.clone().into_keys().collect::<Vec<_>>(),
-        "Betweenness" => betweenness_statistic.clone().into_values().collect::<Vec<_>>(),
-        "Closeness" => closeness_statistic.clone().into_values().collect::<Vec<_>>(),
-    )
-    .unwrap();
-    println!("{}", df);
+    Report::new(&graph).weighted(true).build().unwrap()
 }
 
-#[allow(dead_code)]
+/// Find the cliques among the friends with Girvan–Newman.
 fn friends() {
     let mut graph: Graph<&str, ()> = Graph::new(GraphSpecs::undirected_create_missing());
     _ = graph
@@ -192,4 +207,20 @@ fn friends() {
             Edge::new("Phil", "Adin"),
         ])
         .unwrap();
+
+    let graph: UnGraph<String, f64> = to_petgraph(&graph);
+    let split = girvan_newman(&graph);
+    for (i, level) in split.levels.iter().enumerate() {
+        let marker = if i == split.best { " <- best" } else { "" };
+        println!("{} communities, modularity {:.4}{marker}", level.len(), level.modularity);
+    }
+    let best = &split.levels[split.best];
+    for c in 0..best.len() {
+        let members: Vec<&str> = graph
+            .node_indices()
+            .filter(|v| best.community[v.index()] == c)
+            .map(|v| graph[v].as_str())
+            .collect();
+        println!("{c}: {}", members.join(", "));
+    }
 }
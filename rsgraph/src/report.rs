@@ -0,0 +1,144 @@
+//! One DataFrame with every centrality measure for every node.
+//!
+//! The graph is copied into petgraph with its nodes in name order, so the
+//! measures from `graphcore` come back in the same order as the `Name`
+//! column. graphrs hands its measures back as a `HashMap` keyed by node name,
+//! so those are looked up by name, and a name it left out is an error.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::fs::File;
+use std::hash::Hash;
+use std::path::Path;
+
+use graphcore::connectivity::{Connectivity, Measure};
+use graphcore::degree::{degrees, Degree};
+use graphcore::distance::harmonic;
+use graphcore::pagerank::{pagerank, PageRank};
+use graphcore::scores::Scores;
+use graphrs::algorithms::centrality::{betweenness, closeness};
+use graphrs::Graph;
+use petgraph::graph::EdgeReference;
+use petgraph::{Directed, EdgeType, Undirected};
+use polars::prelude::*;
+
+use crate::convert::to_petgraph;
+
+/// Builds the report for one graph.
+pub struct Report<'a, T, A>
+where
+    T: Hash + Eq + Clone + Ord + Debug + Display + Send + Sync,
+    A: Clone + Debug + Send + Sync,
+{
+    graph: &'a Graph<T, A>,
+    weighted: bool,
+    weighted_closeness: Option<bool>,
+    wf_improved: bool,
+    normalized: bool,
+}
+
+impl<'a, T, A> Report<'a, T, A>
+where
+    T: Hash + Eq + Clone + Ord + Debug + Display + Send + Sync,
+    A: Clone + Debug + Send + Sync,
+{
+    pub fn new(graph: &'a Graph<T, A>) -> Self {
+        Report { graph, weighted: false, weighted_closeness: None, wf_improved: true, normalized: true }
+    }
+
+    /// Use edge weights as distances for closeness, harmonic and betweenness,
+    /// and as transition weights for PageRank.
+    pub fn weighted(mut self, weighted: bool) -> Self {
+        self.weighted = weighted;
+        self
+    }
+
+    /// Override `weighted` for closeness alone. Neo4j GDS ignores the
+    /// weights in closeness even where betweenness uses them.
+    pub fn weighted_closeness(mut self, weighted: bool) -> Self {
+        self.weighted_closeness = Some(weighted);
+        self
+    }
+
+    /// Scale closeness by the fraction of the graph each node reaches, as
+    /// Wasserman and Faust suggest, so that a node that reaches one close
+    /// neighbor doesn't look central. On by default; GDS leaves it off.
+    pub fn wf_improved(mut self, wf_improved: bool) -> Self {
+        self.wf_improved = wf_improved;
+        self
+    }
+
+    /// Divide betweenness by the number of pairs of other nodes. Off gives
+    /// the raw counts that Neo4j GDS reports.
+    pub fn normalized(mut self, normalized: bool) -> Self {
+        self.normalized = normalized;
+        self
+    }
+
+    /// One row per node, sorted by name, with the columns `Name`, `Degree`,
+    /// `InDegree`, `OutDegree`, `Closeness`, `Harmonic`, `Betweenness` and
+    /// `PageRank`. On an undirected graph the in- and out-degree equal the
+    /// degree.
+    pub fn build(&self) -> PolarsResult<DataFrame> {
+        if self.graph.specs.directed {
+            self.build_from(&to_petgraph::<_, _, Directed>(self.graph))
+        } else {
+            self.build_from(&to_petgraph::<_, _, Undirected>(self.graph))
+        }
+    }
+
+    fn build_from<Ty: EdgeType + Sync>(&self, graph: &petgraph::Graph<String, f64, Ty>) -> PolarsResult<DataFrame> {
+        let names: Vec<&str> = graph.node_weights().map(String::as_str).collect();
+        let degree = |degree| degrees(graph, degree).into_iter().map(|k| k as f64).collect::<Vec<f64>>();
+        let in_order = |scores: Scores<String>| graph.node_indices().map(|v| scores[v]).collect::<Vec<f64>>();
+        let by_name = |values: HashMap<T, f64>| {
+            let values: HashMap<String, f64> = values.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+            names
+                .iter()
+                .map(|n| values.get(*n).copied().ok_or_else(|| polars_err!(ComputeError: "no score for node {}", n)))
+                .collect::<PolarsResult<Vec<f64>>>()
+        };
+        let connectivity = Connectivity::of(graph);
+        for measure in [Measure::Closeness, Measure::Harmonic, Measure::Betweenness, Measure::PageRank] {
+            connectivity.warn(measure);
+        }
+        let weighted = self.weighted;
+        let edge_weight = move |e: EdgeReference<f64>| if weighted { *e.weight() } else { 1.0 };
+
+        let betweenness = betweenness::betweenness_centrality(self.graph, self.weighted, self.normalized)
+            .map_err(|e| polars_err!(ComputeError: "betweenness: {:?}", e))?;
+        let weighted_closeness = self.weighted_closeness.unwrap_or(self.weighted);
+        let closeness = closeness::closeness_centrality(self.graph, weighted_closeness, self.wf_improved)
+            .map_err(|e| polars_err!(ComputeError: "closeness: {:?}", e))?;
+        let (pagerank, _) = pagerank(graph, edge_weight, &PageRank::default());
+
+        df!(
+            "Name" => &names,
+            "Degree" => degree(Degree::Total),
+            "InDegree" => degree(Degree::In),
+            "OutDegree" => degree(Degree::Out),
+            "Closeness" => by_name(closeness)?,
+            "Harmonic" => in_order(harmonic(graph, edge_weight, true)),
+            "Betweenness" => by_name(betweenness)?,
+            "PageRank" => in_order(pagerank),
+        )
+    }
+}
+
+/// Sort by `column`, which may be any column of the report.
+pub fn sort(report: DataFrame, column: &str, descending: bool) -> PolarsResult<DataFrame> {
+    report
+        .lazy()
+        .sort([column], SortMultipleOptions::default().with_order_descending(descending))
+        .collect()
+}
+
+/// Write the report as CSV or Parquet, depending on the extension of `path`.
+pub fn export(report: &mut DataFrame, path: &Path) -> PolarsResult<()> {
+    let file = File::create(path)?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("csv") => CsvWriter::new(file).finish(report),
+        Some("parquet") => ParquetWriter::new(file).finish(report).map(|_| ()),
+        _ => Err(polars_err!(InvalidOperation: "expected a .csv or .parquet file, got {}", path.display())),
+    }
+}
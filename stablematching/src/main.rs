@@ -0,0 +1,108 @@
+use std::env;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::process;
+
+mod matching;
+mod rotations;
+mod search;
+
+use matching::Preferences;
+use rotations::Lattice;
+use search::{best, Regret};
+
+const USAGE: &str = "Usage: stablematching [proposers.csv receivers.csv [matching.dot [lattice.dot]]]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let preferences = match &args[..] {
+        [] => gusfield_irving(),
+        [proposers, receivers] | [proposers, receivers, _] | [proposers, receivers, _, _] => {
+            let open = |path: &String| File::open(path).unwrap_or_else(|e| fail(path, e));
+            Preferences::from_csv(open(proposers), open(receivers))
+                .unwrap_or_else(|e| fail(&format!("{proposers}, {receivers}"), e))
+        }
+        _ => usage(),
+    };
+
+    let matching = preferences.gale_shapley();
+    println!("Gale-Shapley, proposers proposing:");
+    for (p, &r) in matching.iter().enumerate() {
+        println!(
+            "  {} - {} (ranks {} and {})",
+            preferences.proposers[p],
+            preferences.receivers[r],
+            preferences.proposer_rank(p, r),
+            preferences.receiver_rank(r, p)
+        );
+    }
+    assert!(preferences.is_stable(&matching));
+
+    let lattice = Lattice::new(&preferences);
+    println!("{} stable matchings and {} rotations:", lattice.len(), lattice.rotations.len());
+    for rotation in &lattice.rotations {
+        println!("  {}", rotation.names(&preferences));
+    }
+    let pairs = |matching: &[usize]| {
+        let pairs: Vec<_> = matching
+            .iter()
+            .enumerate()
+            .map(|(p, &r)| format!("{} - {}", preferences.proposers[p], preferences.receivers[r]))
+            .collect();
+        pairs.join(", ")
+    };
+    for matching in lattice.matchings() {
+        println!("  {} costs {}", pairs(matching), Regret::Total.cost(&preferences, matching));
+    }
+
+    // The same answers by A*, without building the lattice.
+    let fairest = best(&preferences, Regret::Total);
+    println!("Least total regret {} with {}.", fairest.cost, pairs(&fairest.matching));
+    let least = best(&preferences, Regret::Maximum);
+    println!("Least maximum regret {} with {}.", least.cost, pairs(&least.matching));
+
+    if let Some(path) = args.get(2) {
+        fs::write(path, preferences.dot(&matching)).unwrap_or_else(|e| fail(path, e));
+    }
+    if let Some(path) = args.get(3) {
+        let path_vertices: Vec<_> =
+            lattice.graph.node_indices().filter(|&v| fairest.path.contains(&lattice.graph[v])).collect();
+        fs::write(path, lattice.dot(&path_vertices)).unwrap_or_else(|e| fail(path, e));
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(2);
+}
+
+fn fail(path: &str, e: impl Display) -> ! {
+    eprintln!("{path}: {e}");
+    process::exit(1);
+}
+
+/// Gusfield and Irving's 4 x 4 example from `StableMarriage.jl`.
+fn gusfield_irving() -> Preferences {
+    Preferences::numbered(
+        &[&[2, 4, 1, 3], &[3, 1, 4, 2], &[2, 3, 1, 4], &[4, 1, 3, 2]],
+        &[&[2, 1, 4, 3], &[4, 3, 1, 2], &[1, 4, 3, 2], &[2, 1, 4, 3]],
+    )
+    .unwrap()
+}
+
+/// Random complete preference lists from a linear congruential generator.
+#[cfg(test)]
+fn random_preferences(n: usize, seed: u64) -> Preferences {
+    let mut state = seed;
+    let mut shuffled = || {
+        let mut list: Vec<usize> = (1..=n).collect();
+        for i in (1..n).rev() {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            list.swap(i, (state >> 33) as usize % (i + 1));
+        }
+        list
+    };
+    let lists: Vec<Vec<usize>> = (0..2 * n).map(|_| shuffled()).collect();
+    let lists: Vec<&[usize]> = lists.iter().map(Vec::as_slice).collect();
+    Preferences::numbered(&lists[..n], &lists[n..]).unwrap()
+}
@@ -0,0 +1,334 @@
+//! Preferences, the Gale–Shapley algorithm and stability.
+//!
+//! Both sides have the same number of people and everyone ranks everyone on
+//! the other side. A matching is stored the way `StableMarriage.jl` stores
+//! it: `matching[p]` is the receiver that proposer `p` is matched to.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::Read;
+
+use petgraph::dot::{Config, Dot};
+use petgraph::graph::{EdgeReference, UnGraph};
+use petgraph::visit::EdgeRef;
+
+pub type Matching = Vec<usize>;
+
+/// Everyone's complete preference list, most preferred first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Preferences {
+    pub proposers: Vec<String>,
+    pub receivers: Vec<String>,
+    proposer_lists: Vec<Vec<usize>>,
+    /// `proposer_rank[p][r]` is where `r` comes on `p`'s list, from zero.
+    proposer_rank: Vec<Vec<usize>>,
+    receiver_rank: Vec<Vec<usize>>,
+}
+
+#[derive(Debug)]
+pub enum PreferenceError {
+    Csv(csv::Error),
+    /// The lists don't rank everyone on the other side exactly once.
+    Invalid(String),
+}
+
+impl fmt::Display for PreferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PreferenceError::Csv(e) => write!(f, "{e}"),
+            PreferenceError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Error for PreferenceError {}
+
+impl From<csv::Error> for PreferenceError {
+    fn from(e: csv::Error) -> Self {
+        PreferenceError::Csv(e)
+    }
+}
+
+impl Preferences {
+    /// Each side is a list of names, each followed by the names on the other
+    /// side in order of preference.
+    pub fn new(proposers: Vec<(String, Vec<String>)>, receivers: Vec<(String, Vec<String>)>) -> Result<Self, PreferenceError> {
+        if proposers.len() != receivers.len() {
+            return Err(PreferenceError::Invalid(format!(
+                "{} proposers but {} receivers",
+                proposers.len(),
+                receivers.len()
+            )));
+        }
+        let proposer_names: Vec<String> = proposers.iter().map(|(name, _)| name.clone()).collect();
+        let receiver_names: Vec<String> = receivers.iter().map(|(name, _)| name.clone()).collect();
+        let proposer_lists = lists(&proposers, &receiver_names)?;
+        let receiver_lists = lists(&receivers, &proposer_names)?;
+        Ok(Preferences {
+            proposers: proposer_names,
+            receivers: receiver_names,
+            proposer_rank: proposer_lists.iter().map(|list| ranks(list)).collect(),
+            receiver_rank: receiver_lists.iter().map(|list| ranks(list)).collect(),
+            proposer_lists,
+        })
+    }
+
+    /// Preference lists numbered from one, as in Gusfield and Irving and
+    /// `StableMarriage.jl`. Proposers are called p1, p2, … and receivers r1,
+    /// r2, ….
+    pub fn numbered(proposers: &[&[usize]], receivers: &[&[usize]]) -> Result<Self, PreferenceError> {
+        let side = |prefix, other, lists: &[&[usize]]| {
+            lists
+                .iter()
+                .enumerate()
+                .map(|(i, list)| (format!("{prefix}{}", i + 1), list.iter().map(|j| format!("{other}{j}")).collect()))
+                .collect()
+        };
+        Preferences::new(side("p", "r", proposers), side("r", "p", receivers))
+    }
+
+    /// Read each side from CSV with no header row. Every record is a name
+    /// followed by that person's preferences, most preferred first.
+    pub fn from_csv<P: Read, R: Read>(proposers: P, receivers: R) -> Result<Self, PreferenceError> {
+        Preferences::new(read_lists(proposers)?, read_lists(receivers)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.proposers.len()
+    }
+
+    /// Where `p` ranks `r`, counting the first choice as 1.
+    pub fn proposer_rank(&self, p: usize, r: usize) -> usize {
+        self.proposer_rank[p][r] + 1
+    }
+
+    /// Where `r` ranks `p`, counting the first choice as 1.
+    pub fn receiver_rank(&self, r: usize, p: usize) -> usize {
+        self.receiver_rank[r][p] + 1
+    }
+
+    /// `r` would rather have `p` than `q`.
+    pub(crate) fn receiver_prefers(&self, r: usize, p: usize, q: usize) -> bool {
+        self.receiver_rank[r][p] < self.receiver_rank[r][q]
+    }
+
+    /// `p`'s preference list, most preferred first.
+    pub(crate) fn proposer_list(&self, p: usize) -> &[usize] {
+        &self.proposer_lists[p]
+    }
+
+    /// The Gale–Shapley algorithm with the proposers proposing. Everyone free
+    /// proposes to the best receiver who hasn't turned them down yet, and each
+    /// receiver holds on to the best offer so far. The result is stable, and
+    /// every proposer does as well as in any stable matching.
+    pub fn gale_shapley(&self) -> Matching {
+        let n = self.len();
+        let mut next = vec![0; n];
+        let mut held: Vec<Option<usize>> = vec![None; n];
+        let mut free: Vec<usize> = (0..n).rev().collect();
+        while let Some(p) = free.pop() {
+            let r = self.proposer_lists[p][next[p]];
+            next[p] += 1;
+            match held[r] {
+                Some(q) if self.receiver_prefers(r, q, p) => free.push(p),
+                Some(q) => {
+                    held[r] = Some(p);
+                    free.push(q);
+                }
+                None => held[r] = Some(p),
+            }
+        }
+        inverse(&held.into_iter().map(Option::unwrap).collect::<Vec<_>>())
+    }
+
+    /// Gale–Shapley with the receivers proposing: the stable matching that is
+    /// best for every receiver and worst for every proposer.
+    pub fn receiver_optimal(&self) -> Matching {
+        let receivers: Vec<(String, Vec<String>)> = (0..self.len()).map(|r| self.list_of_receiver(r)).collect();
+        let proposers: Vec<(String, Vec<String>)> = (0..self.len())
+            .map(|p| (self.proposers[p].clone(), self.proposer_lists[p].iter().map(|&r| self.receivers[r].clone()).collect()))
+            .collect();
+        let swapped = Preferences::new(receivers, proposers).expect("already checked");
+        inverse(&swapped.gale_shapley())
+    }
+
+    fn list_of_receiver(&self, r: usize) -> (String, Vec<String>) {
+        let mut list: Vec<usize> = (0..self.len()).collect();
+        list.sort_by_key(|&p| self.receiver_rank[r][p]);
+        (self.receivers[r].clone(), list.into_iter().map(|p| self.proposers[p].clone()).collect())
+    }
+
+    /// Pairs (p, r) not matched to each other who would both rather be. A
+    /// matching is stable when there are none.
+    pub fn blocking_pairs(&self, matching: &[usize]) -> Vec<(usize, usize)> {
+        let partner = inverse(matching);
+        let mut pairs = Vec::new();
+        for (p, &current) in matching.iter().enumerate() {
+            for &r in &self.proposer_lists[p] {
+                if r == current {
+                    break;
+                }
+                if self.receiver_prefers(r, p, partner[r]) {
+                    pairs.push((p, r));
+                }
+            }
+        }
+        pairs
+    }
+
+    pub fn is_stable(&self, matching: &[usize]) -> bool {
+        self.blocking_pairs(matching).is_empty()
+    }
+
+    /// The matching in DOT, labelling both ends of each pair with the rank
+    /// that side gave the other. Blocking pairs are drawn dashed and red.
+    pub fn dot(&self, matching: &[usize]) -> String {
+        let mut graph = UnGraph::new_undirected();
+        let proposers: Vec<_> = self.proposers.iter().map(|name| graph.add_node(name.as_str())).collect();
+        let receivers: Vec<_> = self.receivers.iter().map(|name| graph.add_node(name.as_str())).collect();
+        let mut blocking = Vec::new();
+        for (p, &r) in matching.iter().enumerate() {
+            graph.add_edge(proposers[p], receivers[r], "");
+            blocking.push(false);
+        }
+        for (p, r) in self.blocking_pairs(matching) {
+            graph.add_edge(proposers[p], receivers[r], "");
+            blocking.push(true);
+        }
+        let edge = |_, e: EdgeReference<&str>| {
+            let (p, r) = (e.source().index(), e.target().index() - self.len());
+            let ranks = format!("taillabel = \"{}\" headlabel = \"{}\"", self.proposer_rank(p, r), self.receiver_rank(r, p));
+            if blocking[e.id().index()] {
+                format!("{ranks} style = dashed color = red")
+            } else {
+                format!("{ranks} penwidth = 2")
+            }
+        };
+        let node = |_, _| String::new();
+        let dot = Dot::with_attr_getters(&graph, &[Config::EdgeNoLabel], &edge, &node);
+        format!("{dot}")
+    }
+}
+
+/// Who is matched to whom, the other way round.
+pub fn inverse(matching: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![0; matching.len()];
+    for (p, &r) in matching.iter().enumerate() {
+        inverse[r] = p;
+    }
+    inverse
+}
+
+fn ranks(list: &[usize]) -> Vec<usize> {
+    let mut rank = vec![0; list.len()];
+    for (i, &x) in list.iter().enumerate() {
+        rank[x] = i;
+    }
+    rank
+}
+
+fn lists(side: &[(String, Vec<String>)], others: &[String]) -> Result<Vec<Vec<usize>>, PreferenceError> {
+    let index: HashMap<&str, usize> = others.iter().enumerate().map(|(i, name)| (name.as_str(), i)).collect();
+    if index.len() < others.len() {
+        return Err(PreferenceError::Invalid("the same name appears twice on one side".into()));
+    }
+    side.iter()
+        .map(|(name, list)| {
+            let mut seen = vec![false; others.len()];
+            let list = list
+                .iter()
+                .map(|other| match index.get(other.as_str()) {
+                    Some(&i) if !seen[i] => {
+                        seen[i] = true;
+                        Ok(i)
+                    }
+                    Some(_) => Err(PreferenceError::Invalid(format!("{name} ranks {other} twice"))),
+                    None => Err(PreferenceError::Invalid(format!("{name} ranks {other}, who isn't on the other side"))),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if list.len() < others.len() {
+                return Err(PreferenceError::Invalid(format!("{name} doesn't rank everyone")));
+            }
+            Ok(list)
+        })
+        .collect()
+}
+
+fn read_lists<R: Read>(reader: R) -> Result<Vec<(String, Vec<String>)>, PreferenceError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let mut lists = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let mut fields = record.iter().map(String::from);
+        if let Some(name) = fields.next() {
+            lists.push((name, fields.filter(|x| !x.is_empty()).collect()));
+        }
+    }
+    Ok(lists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gusfield_irving;
+
+    /// Matchings in `StableMarriage.jl` are numbered from one.
+    fn from_one(matching: &[usize]) -> Matching {
+        matching.iter().map(|r| r - 1).collect()
+    }
+
+    #[test]
+    fn stable_marriage_jl() {
+        let preferences = gusfield_irving();
+        assert!(preferences.is_stable(&from_one(&[4, 3, 2, 1])));
+        assert!(preferences.is_stable(&from_one(&[4, 1, 2, 3])));
+        assert!(!preferences.is_stable(&from_one(&[1, 3, 2, 4])));
+        assert_eq!(preferences.gale_shapley(), from_one(&[4, 3, 2, 1]));
+        assert_eq!(preferences.receiver_optimal(), from_one(&[4, 1, 2, 3]));
+
+        // Gale and Shapley's 3 x 3 example, where every cyclic shift is stable
+        // and no other matching is.
+        let preferences =
+            Preferences::numbered(&[&[1, 2, 3], &[2, 3, 1], &[3, 1, 2]], &[&[2, 3, 1], &[3, 1, 2], &[1, 2, 3]]).unwrap();
+        for (matching, stable) in [
+            ([1, 2, 3], true), ([3, 1, 2], true), ([2, 3, 1], true),
+            ([1, 3, 2], false), ([3, 2, 1], false), ([2, 1, 3], false),
+        ] {
+            assert_eq!(preferences.is_stable(&from_one(&matching)), stable);
+        }
+        assert_eq!(preferences.gale_shapley(), from_one(&[1, 2, 3]));
+        assert_eq!(preferences.receiver_optimal(), from_one(&[3, 1, 2]));
+    }
+
+    #[test]
+    fn csv_names() {
+        let proposers = "Ann, Xavier, Yusuf, Zoe\nBea, Xavier, Yusuf, Zoe\nCal, Xavier, Yusuf, Zoe\n";
+        let receivers = "Xavier, Bea, Ann, Cal\nYusuf, Ann, Bea, Cal\nZoe, Ann, Bea, Cal\n";
+        let preferences = Preferences::from_csv(proposers.as_bytes(), receivers.as_bytes()).unwrap();
+        let matching = preferences.gale_shapley();
+        let pairs: Vec<_> = matching.iter().enumerate().map(|(p, &r)| (&*preferences.proposers[p], &*preferences.receivers[r])).collect();
+        assert_eq!(pairs, [("Ann", "Yusuf"), ("Bea", "Xavier"), ("Cal", "Zoe")]);
+        assert_eq!(preferences.proposer_rank(0, 1), 2);
+
+        let error = Preferences::from_csv("Ann, Xavier, Xavier".as_bytes(), "Xavier, Ann".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "Ann ranks Xavier twice");
+        let error = Preferences::from_csv("Ann, Xavier".as_bytes(), "Xavier, Bob".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "Xavier ranks Bob, who isn't on the other side");
+    }
+
+    #[test]
+    fn dot_shows_blocking_pairs() {
+        let preferences = gusfield_irving();
+        let dot = preferences.dot(&from_one(&[1, 3, 2, 4]));
+        assert!(dot.starts_with("graph {"));
+        assert_eq!(dot.matches("penwidth = 2").count(), 4);
+        assert_eq!(dot.matches("color = red").count(), preferences.blocking_pairs(&from_one(&[1, 3, 2, 4])).len());
+        // p1 ranks r1 third, r1 ranks p1 second.
+        assert!(dot.contains("0 -- 4 [ taillabel = \"3\" headlabel = \"2\" penwidth = 2]"));
+    }
+}
@@ -0,0 +1,226 @@
+//! Every stable matching, found by eliminating rotations.
+//!
+//! Following Gusfield and Irving, in a stable matching M let s(p) be the
+//! first receiver after M(p) on p's list who would rather have p than her
+//! partner in M, and next(p) that partner. A rotation is a cycle
+//! p0 -> next(p0) -> … -> p0. Eliminating it moves each p_i to s(p_i), which
+//! is the receiver p_(i+1) had, and gives another stable matching. Starting
+//! from the proposer-optimal matching and eliminating rotations in every
+//! possible order reaches each stable matching, ending at the
+//! receiver-optimal one. Together they form a distributive lattice.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use petgraph::dot::Dot;
+use petgraph::graph::{DiGraph, NodeIndex};
+
+use crate::matching::{inverse, Matching, Preferences};
+
+/// Proposer and receiver pairs (p_i, M(p_i)) in cycle order, starting from
+/// the lowest numbered proposer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rotation(pub Vec<(usize, usize)>);
+
+impl Rotation {
+    /// The matching with this rotation eliminated.
+    pub fn eliminate(&self, matching: &[usize]) -> Matching {
+        let mut matching = matching.to_vec();
+        let k = self.0.len();
+        for i in 0..k {
+            matching[self.0[i].0] = self.0[(i + 1) % k].1;
+        }
+        matching
+    }
+
+    /// Names of the pairs, as in Gusfield and Irving's (p1, r2), (p3, r4).
+    pub fn names(&self, preferences: &Preferences) -> String {
+        let pairs: Vec<_> = self
+            .0
+            .iter()
+            .map(|&(p, r)| format!("({}, {})", preferences.proposers[p], preferences.receivers[r]))
+            .collect();
+        pairs.join(", ")
+    }
+}
+
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pairs: Vec<_> = self.0.iter().map(|(p, r)| format!("({p}, {r})")).collect();
+        f.write_str(&pairs.join(", "))
+    }
+}
+
+/// The rotations exposed in a stable matching, ordered by their lowest
+/// numbered proposer.
+pub fn exposed_rotations(preferences: &Preferences, matching: &[usize]) -> Vec<Rotation> {
+    let n = matching.len();
+    let partner = inverse(matching);
+    let next: Vec<Option<usize>> = (0..n)
+        .map(|p| {
+            let list = preferences.proposer_list(p);
+            let current = list.iter().position(|&r| r == matching[p]).unwrap();
+            list[current + 1..]
+                .iter()
+                .find(|&&r| preferences.receiver_prefers(r, p, partner[r]))
+                .map(|&r| partner[r])
+        })
+        .collect();
+
+    // next is a partial function, so each walk ends either where next is
+    // undefined, at a vertex an earlier walk visited or on a new cycle.
+    let mut walked = vec![usize::MAX; n];
+    let mut rotations = Vec::new();
+    for start in 0..n {
+        let mut p = start;
+        while walked[p] == usize::MAX {
+            walked[p] = start;
+            match next[p] {
+                Some(q) => p = q,
+                None => break,
+            }
+        }
+        if walked[p] == start && next[p].is_some() {
+            let mut cycle = vec![p];
+            let mut q = next[p].unwrap();
+            while q != p {
+                cycle.push(q);
+                q = next[q].unwrap();
+            }
+            let first = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap();
+            cycle.rotate_left(first);
+            rotations.push(Rotation(cycle.into_iter().map(|p| (p, matching[p])).collect()));
+        }
+    }
+    rotations.sort_by_key(|rotation| rotation.0[0].0);
+    rotations
+}
+
+/// All stable matchings. Vertex 0 is the proposer-optimal matching, and an
+/// edge M -> M' holds the index of the rotation whose elimination turns M
+/// into M'.
+#[derive(Clone, Debug)]
+pub struct Lattice {
+    pub graph: DiGraph<Matching, usize>,
+    pub rotations: Vec<Rotation>,
+}
+
+impl Lattice {
+    /// Breadth-first from the proposer-optimal matching, so matchings are
+    /// numbered by how many rotations were eliminated to reach them.
+    pub fn new(preferences: &Preferences) -> Self {
+        let mut graph = DiGraph::new();
+        let mut rotations = Vec::new();
+        let mut rotation_index: HashMap<Rotation, usize> = HashMap::new();
+        let first = preferences.gale_shapley();
+        let mut index = HashMap::from([(first.clone(), graph.add_node(first))]);
+        let mut queue = VecDeque::from([NodeIndex::new(0)]);
+        while let Some(v) = queue.pop_front() {
+            for rotation in exposed_rotations(preferences, &graph[v]) {
+                let next = rotation.eliminate(&graph[v]);
+                let w = *index.entry(next.clone()).or_insert_with(|| {
+                    let w = graph.add_node(next);
+                    queue.push_back(w);
+                    w
+                });
+                let k = rotations.len();
+                let r = *rotation_index.entry(rotation.clone()).or_insert(k);
+                if r == k {
+                    rotations.push(rotation);
+                }
+                graph.add_edge(v, w, r);
+            }
+        }
+        Lattice { graph, rotations }
+    }
+
+    pub fn len(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn matchings(&self) -> impl Iterator<Item = &Matching> {
+        self.graph.node_weights()
+    }
+
+    /// The lattice in DOT, with matchings numbered from one as in
+    /// `gale-shapley-ex2.dot` and the vertices in `highlight` drawn blue.
+    pub fn dot(&self, highlight: &[NodeIndex]) -> String {
+        let graph = self.graph.map(
+            |_, matching| format!("{:?}", matching.iter().map(|r| r + 1).collect::<Vec<_>>()),
+            |_, &rotation| rotation,
+        );
+        let edge = |_, _| String::new();
+        let node = |_, (v, _)| {
+            if highlight.contains(&v) { "color = blue".to_string() } else { String::new() }
+        };
+        let dot = Dot::with_attr_getters(&graph, &[], &edge, &node);
+        format!("{dot}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gusfield_irving, random_preferences};
+
+    /// Every stable matching by trying all n! of them.
+    fn brute_force(preferences: &Preferences) -> Vec<Matching> {
+        fn permute(matching: &mut Matching, k: usize, preferences: &Preferences, stable: &mut Vec<Matching>) {
+            if k == matching.len() {
+                if preferences.is_stable(matching) {
+                    stable.push(matching.clone());
+                }
+                return;
+            }
+            for i in k..matching.len() {
+                matching.swap(k, i);
+                permute(matching, k + 1, preferences, stable);
+                matching.swap(k, i);
+            }
+        }
+        let mut stable = Vec::new();
+        permute(&mut (0..preferences.len()).collect(), 0, preferences, &mut stable);
+        stable.sort();
+        stable
+    }
+
+    #[test]
+    fn gusfield_irving_example() {
+        let preferences = gusfield_irving();
+        let lattice = Lattice::new(&preferences);
+        assert_eq!(lattice.len(), 2);
+        assert_eq!(lattice.rotations.len(), 1);
+        // p2 and p4 swap r3 and r1.
+        assert_eq!(lattice.rotations[0].names(&preferences), "(p2, r3), (p4, r1)");
+        assert_eq!(lattice.graph[NodeIndex::new(1)], preferences.receiver_optimal());
+    }
+
+    #[test]
+    fn gale_shapley_cycle() {
+        // Every proposer gets their first, second or third choice.
+        let preferences =
+            Preferences::numbered(&[&[1, 2, 3], &[2, 3, 1], &[3, 1, 2]], &[&[2, 3, 1], &[3, 1, 2], &[1, 2, 3]]).unwrap();
+        let lattice = Lattice::new(&preferences);
+        assert_eq!(lattice.len(), 3);
+        assert_eq!(lattice.rotations.len(), 2);
+        assert_eq!(lattice.rotations[0].to_string(), "(0, 0), (1, 1), (2, 2)");
+        assert_eq!(lattice.graph.edge_count(), 2);
+    }
+
+    #[test]
+    fn agrees_with_brute_force() {
+        for seed in 0..20 {
+            let preferences = random_preferences(7, seed);
+            let lattice = Lattice::new(&preferences);
+            let mut matchings: Vec<Matching> = lattice.matchings().cloned().collect();
+            matchings.sort();
+            assert_eq!(matchings, brute_force(&preferences), "seed {seed}");
+            // Each rotation is eliminated exactly once on any path from the
+            // top of the lattice to the bottom.
+            let bottom = lattice.graph.node_indices().find(|&v| lattice.graph.neighbors(v).next().is_none()).unwrap();
+            assert_eq!(lattice.graph[bottom], preferences.receiver_optimal());
+            let path = petgraph::algo::astar(&lattice.graph, NodeIndex::new(0), |v| v == bottom, |_| 1, |_| 0);
+            assert_eq!(path.unwrap().0, lattice.rotations.len());
+        }
+    }
+}
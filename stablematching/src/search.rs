@@ -0,0 +1,168 @@
+//! The best stable matching by A* search over the lattice of stable
+//! matchings, in the spirit of `StableMarriageSearch.jl`.
+//!
+//! Gale–Shapley favors the proposers. To be fair to both sides, look for the
+//! stable matching that minimizes a cost, where everyone's rank of their
+//! partner counts from 1 for a first choice. Moving down the lattice only
+//! makes proposers worse off and receivers better off, which gives an
+//! admissible heuristic: proposers' ranks as they are now, receivers' ranks
+//! as in the receiver-optimal matching.
+//!
+//! The lattice can have exponentially many matchings, so the search never
+//! builds it. Each matching's successors come from eliminating its exposed
+//! rotations, and only matchings the heuristic can't rule out get expanded.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+use crate::matching::{inverse, Matching, Preferences};
+use crate::rotations::exposed_rotations;
+
+/// What the search minimizes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Regret {
+    /// The egalitarian cost: the sum of everyone's rank of their partner.
+    #[default]
+    Total,
+    /// The worst rank anyone gets, on either side.
+    Maximum,
+}
+
+impl Regret {
+    pub fn cost(self, preferences: &Preferences, matching: &[usize]) -> usize {
+        let (proposers, receivers) = ranks(preferences, matching);
+        self.combine(proposers.chain(receivers))
+    }
+
+    fn combine(self, ranks: impl Iterator<Item = usize>) -> usize {
+        match self {
+            Regret::Total => ranks.sum(),
+            Regret::Maximum => ranks.max().unwrap_or(0),
+        }
+    }
+}
+
+fn ranks<'a>(
+    preferences: &'a Preferences,
+    matching: &'a [usize],
+) -> (impl Iterator<Item = usize> + 'a, impl Iterator<Item = usize> + 'a) {
+    let partner = inverse(matching);
+    let proposers = matching.iter().enumerate().map(|(p, &r)| preferences.proposer_rank(p, r));
+    let receivers = (0..matching.len()).map(move |r| preferences.receiver_rank(r, partner[r]));
+    (proposers, receivers)
+}
+
+/// The result of a search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Best {
+    pub matching: Matching,
+    pub cost: usize,
+    /// Matchings from the proposer-optimal one to the best one, each the
+    /// previous with one rotation eliminated.
+    pub path: Vec<Matching>,
+    /// Number of matchings whose rotations were eliminated.
+    pub expanded: usize,
+}
+
+/// A* from the proposer-optimal matching. Eliminating a rotation costs
+/// nothing, and stopping at a matching costs as much as the matching does,
+/// so the first matching taken off the queue to stop at is the best one.
+/// Ties go to the matching found first.
+pub fn best(preferences: &Preferences, regret: Regret) -> Best {
+    let receiver_optimal = preferences.receiver_optimal();
+    let (_, receivers) = ranks(preferences, &receiver_optimal);
+    let receivers: Vec<usize> = receivers.collect();
+    let estimate = |matching: &[usize]| {
+        let (proposers, _) = ranks(preferences, matching);
+        regret.combine(proposers.chain(receivers.iter().copied()))
+    };
+
+    // Every matching found, with the one it was found from.
+    let mut found: Vec<(Matching, Option<usize>)> = Vec::new();
+    let mut seen = HashSet::new();
+    // (priority, stop, order found); stopping sorts before expanding.
+    let mut queue = BinaryHeap::new();
+    let first = preferences.gale_shapley();
+    queue.push(Reverse((estimate(&first), 1, 0)));
+    seen.insert(first.clone());
+    found.push((first, None));
+    let mut expanded = 0;
+    while let Some(Reverse((cost, stop, i))) = queue.pop() {
+        if stop == 0 {
+            let mut path = vec![found[i].0.clone()];
+            let mut parent = found[i].1;
+            while let Some(j) = parent {
+                path.push(found[j].0.clone());
+                parent = found[j].1;
+            }
+            path.reverse();
+            return Best { matching: found[i].0.clone(), cost, path, expanded };
+        }
+        expanded += 1;
+        queue.push(Reverse((regret.cost(preferences, &found[i].0), 0, i)));
+        for rotation in exposed_rotations(preferences, &found[i].0) {
+            let next = rotation.eliminate(&found[i].0);
+            if seen.insert(next.clone()) {
+                queue.push(Reverse((estimate(&next), 1, found.len())));
+                found.push((next, Some(i)));
+            }
+        }
+    }
+    unreachable!("the proposer-optimal matching is always stable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rotations::Lattice;
+    use crate::{gusfield_irving, random_preferences};
+
+    #[test]
+    fn gusfield_irving_example() {
+        // The proposer-optimal [4, 3, 2, 1] costs 17, [4, 1, 2, 3] costs 15.
+        let preferences = gusfield_irving();
+        let best = best(&preferences, Regret::Total);
+        assert_eq!(best.matching, [3, 0, 1, 2]);
+        assert_eq!(best.cost, 15);
+        assert_eq!(best.path, [preferences.gale_shapley(), best.matching.clone()]);
+        assert_eq!(Regret::Total.cost(&preferences, &preferences.gale_shapley()), 17);
+        assert_eq!(Regret::Maximum.cost(&preferences, &preferences.gale_shapley()), 4);
+    }
+
+    #[test]
+    fn minimum_regret_is_in_the_middle() {
+        // Gale and Shapley's example: the two optimal matchings give one side
+        // everyone's third choice, but the one between them gives everyone
+        // their second.
+        let preferences =
+            Preferences::numbered(&[&[1, 2, 3], &[2, 3, 1], &[3, 1, 2]], &[&[2, 3, 1], &[3, 1, 2], &[1, 2, 3]]).unwrap();
+        let best = best(&preferences, Regret::Maximum);
+        assert_eq!(best.matching, [1, 2, 0]);
+        assert_eq!(best.cost, 2);
+        // All three have a total cost of 12.
+        assert_eq!(super::best(&preferences, Regret::Total).cost, 12);
+    }
+
+    #[test]
+    fn agrees_with_every_matching() {
+        let (mut expanded, mut matchings) = (0, 0);
+        for seed in 0..20 {
+            let preferences = random_preferences(8, seed);
+            let lattice = Lattice::new(&preferences);
+            for regret in [Regret::Total, Regret::Maximum] {
+                let cheapest = lattice.matchings().map(|m| regret.cost(&preferences, m)).min().unwrap();
+                let best = best(&preferences, regret);
+                assert_eq!(best.cost, cheapest, "seed {seed}");
+                assert_eq!(regret.cost(&preferences, &best.matching), cheapest);
+                assert!(preferences.is_stable(&best.matching));
+                for pair in best.path.windows(2) {
+                    assert!(exposed_rotations(&preferences, &pair[0]).iter().any(|r| r.eliminate(&pair[0]) == pair[1]));
+                }
+                expanded += best.expanded;
+                matchings += lattice.len();
+            }
+        }
+        // The heuristic rules out some matchings without expanding them.
+        assert!(expanded < matchings, "{expanded} of {matchings}");
+    }
+}